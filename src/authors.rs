@@ -1,4 +1,11 @@
-pub fn handle() -> anyhow::Result<()> {
+use crate::global_args;
+
+pub fn handle(global_arguments: &global_args::GlobalArgs) -> anyhow::Result<()> {
+	if global_arguments.json {
+		log_mdc::insert("authors", clap::crate_authors!());
+		return Ok(());
+	}
+
 	println!("Authors: {}", clap::crate_authors!());
 
 	Ok(())
@@ -0,0 +1,28 @@
+use clap::Args;
+use clap_complete::Shell;
+use log::trace;
+
+use crate::global_args;
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+	/// Shell to generate the completion script for
+	#[arg(value_enum)]
+	pub shell: Shell,
+}
+
+/// Print the completion script of the given command line definition to stdout, e.g.
+/// `saas-template-companion completions zsh > ~/.zfunc/_saas-template-companion`
+pub fn handle(
+	global_arguments: &global_args::GlobalArgs,
+	arguments: &CompletionsArgs,
+	command: &mut clap::Command,
+) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	trace!("{:?}", arguments);
+
+	let name = command.get_name().to_owned();
+	clap_complete::generate(arguments.shell, command, name, &mut std::io::stdout());
+
+	Ok(())
+}
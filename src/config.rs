@@ -0,0 +1,270 @@
+//! Project configuration, stored in `companion.toml` at the root of the project.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Command;
+use serde::{Deserialize, Serialize};
+
+use crate::error::CompanionError;
+use crate::global_args::{DEFAULT_LOG_FILE_COUNT, DEFAULT_LOG_FILE_MAX_SIZE};
+
+/// Name of the configuration file, looked up from the current working directory
+pub const DEFAULT_CONFIG_FILE: &str = "companion.toml";
+
+/// Path of the configuration file to load, the one given with `--config` or `companion.toml` when
+/// it exists
+pub fn resolve_path(config: Option<&Path>) -> Option<PathBuf> {
+	config.map(Path::to_path_buf)
+	      .or_else(|| Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|path| path.exists()))
+}
+
+/// Settings of the `make keys` command
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+	/// Default name of the secret when using the kubernetes-secret output
+	pub kubernetes_secret_name: String,
+}
+
+impl Default for KeysConfig {
+	fn default() -> Self {
+		Self {
+			kubernetes_secret_name: "saas-template-keys".to_owned(),
+		}
+	}
+}
+
+/// Settings of the log file, shared by every command
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+	/// Default of `--log-file`, the logs are only written to the console when not set
+	pub file: Option<PathBuf>,
+	/// Default of `--log-file-max-size`
+	pub size: u64,
+	/// Default of `--log-file-count`
+	pub count: u32,
+}
+
+impl Default for LogConfig {
+	fn default() -> Self {
+		Self {
+			file: None,
+			size: DEFAULT_LOG_FILE_MAX_SIZE,
+			count: DEFAULT_LOG_FILE_COUNT,
+		}
+	}
+}
+
+/// Configuration of the companion for a project
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompanionConfig {
+	/// Default environment file of the commands taking an `--env` option
+	pub env: PathBuf,
+	/// Default example environment file of the commands taking an `--example` option
+	pub example: PathBuf,
+	/// Default schema of the commands taking a `--schema` option
+	pub schema: PathBuf,
+	/// Settings of the `make keys` command
+	pub keys: KeysConfig,
+	/// Settings of the log file
+	pub log: LogConfig,
+}
+
+impl Default for CompanionConfig {
+	fn default() -> Self {
+		Self {
+			env: PathBuf::from(".env"),
+			example: PathBuf::from(".env.example"),
+			schema: PathBuf::from("env.schema.toml"),
+			keys: KeysConfig::default(),
+			log: LogConfig::default(),
+		}
+	}
+}
+
+/// Quote a value as a TOML string
+fn toml_string(value: &str) -> String {
+	toml::Value::String(value.to_owned()).to_string()
+}
+
+impl CompanionConfig {
+	/// Load the configuration from a TOML file, missing settings get their default value
+	pub fn load(path: &Path) -> anyhow::Result<Self> {
+		let content = std::fs::read_to_string(path)
+			.with_context(|| format!("Something went wrong while reading {}", path.display()))?;
+
+		toml::from_str(&content)
+			.map_err(|error| CompanionError::Config(format!("Invalid configuration {}: {}", path.display(), error.message())).into())
+	}
+
+	/// Load the configuration file given with `--config`, or `companion.toml` when it exists, the
+	/// defaults are used without any configuration file
+	pub fn resolve(config: Option<&Path>) -> anyhow::Result<Self> {
+		match resolve_path(config) {
+			Some(path) => Self::load(&path),
+			None => Ok(Self::default()),
+		}
+	}
+
+	/// Use the configured settings as the default values of the matching options of every command,
+	/// the options given on the command line still take precedence
+	pub fn apply_defaults(&self, command: Command) -> Command {
+		let names: Vec<String> = command.get_subcommands()
+		                                .map(|subcommand| subcommand.get_name().to_owned())
+		                                .collect();
+
+		let command = command.mut_args(|arg| {
+			match (arg.get_id().as_str(), &self.log.file) {
+				("log_file", Some(file)) => return arg.default_value(file.clone().into_os_string()),
+				("log_file_max_size", _) => return arg.default_value(self.log.size.to_string()),
+				("log_file_count", _) => return arg.default_value(self.log.count.to_string()),
+				_ => {}
+			}
+
+			// only the options defaulting to a file are configurable, e.g. not `env decrypt --output`
+			if arg.get_default_values().is_empty() {
+				return arg;
+			}
+
+			match arg.get_id().as_str() {
+				"env" => arg.default_value(self.env.clone().into_os_string()),
+				"example" => arg.default_value(self.example.clone().into_os_string()),
+				"schema" => arg.default_value(self.schema.clone().into_os_string()),
+				"kubernetes_secret_name" => arg.default_value(self.keys.kubernetes_secret_name.clone()),
+				_ => arg,
+			}
+		});
+
+		names.iter()
+		     .fold(command, |command, name| command.mut_subcommand(name, |subcommand| self.apply_defaults(subcommand)))
+	}
+
+	/// Render the configuration as a commented TOML file
+	pub fn render(&self) -> String {
+		format!(
+			r#"# SaaS Template Companion configuration, see `saas-template-companion --help`
+
+# Default environment file of the commands, `--env` takes precedence
+env = {}
+
+# Example environment file, committed with the project, new environment files are created from it,
+# default of `--example`
+example = {}
+
+# Schema declaring the type and requirement of the environment variables, used by `env check`
+# and `make env-types`, default of `--schema`
+schema = {}
+
+[keys]
+# Default name of the secret when using `make keys --output kubernetes-secret`
+kubernetes_secret_name = {}
+
+[log]
+# File the logs are also written to, default of `--log-file`
+{}
+# Size in bytes after which the log file is rotated, default of `--log-file-max-size`
+size = {}
+# Number of rotated log files to keep, default of `--log-file-count`
+count = {}
+"#,
+			toml_string(&self.env.display().to_string()),
+			toml_string(&self.example.display().to_string()),
+			toml_string(&self.schema.display().to_string()),
+			toml_string(&self.keys.kubernetes_secret_name),
+			match &self.log.file {
+				Some(file) => format!("file = {}", toml_string(&file.display().to_string())),
+				None => "# file = \"companion.log\"".to_owned(),
+			},
+			self.log.size,
+			self.log.count,
+		)
+	}
+}
+
+#[test]
+fn can_render_and_load_configurations() {
+	use assert_fs::prelude::*;
+
+	let config = CompanionConfig {
+		env: PathBuf::from("apps/web/.env"),
+		..CompanionConfig::default()
+	};
+
+	let file = assert_fs::NamedTempFile::new(DEFAULT_CONFIG_FILE).unwrap();
+	file.write_str(&config.render()).unwrap();
+	assert_eq!(CompanionConfig::load(file.path()).unwrap(), config);
+
+	let config = CompanionConfig {
+		log: LogConfig {
+			file: Some(PathBuf::from("logs/companion.log")),
+			size: 1024,
+			count: 2,
+		},
+		..CompanionConfig::default()
+	};
+	file.write_str(&config.render()).unwrap();
+	assert_eq!(CompanionConfig::load(file.path()).unwrap(), config);
+
+	file.write_str("unknown = true\n").unwrap();
+	let error = CompanionConfig::load(file.path()).unwrap_err();
+	assert_eq!(crate::error::classify(&error).0, crate::error::ErrorKind::Config);
+}
+
+#[test]
+fn can_apply_configured_defaults() {
+	let config = CompanionConfig {
+		env: PathBuf::from("apps/web/.env"),
+		..CompanionConfig::default()
+	};
+
+	let command = config.apply_defaults(
+		Command::new("companion").subcommand(
+			Command::new("env").subcommand(
+				Command::new("set")
+					.arg(clap::Arg::new("env").long("env").default_value(".env"))
+					.arg(clap::Arg::new("output").long("output").default_value(".env"))
+			)
+		)
+	);
+
+	let matches = command.get_matches_from(["companion", "env", "set"]);
+	let (_, env_matches) = matches.subcommand().unwrap();
+	let (_, set_matches) = env_matches.subcommand().unwrap();
+	assert_eq!(set_matches.get_one::<String>("env").unwrap(), "apps/web/.env");
+	assert_eq!(set_matches.get_one::<String>("output").unwrap(), ".env");
+}
+
+#[test]
+fn can_apply_configured_log_defaults() {
+	use clap::{Args, FromArgMatches};
+
+	let config = CompanionConfig {
+		log: LogConfig {
+			file: Some(PathBuf::from("logs/companion.log")),
+			size: 1024,
+			count: 2,
+		},
+		..CompanionConfig::default()
+	};
+	let command = config.apply_defaults(crate::global_args::GlobalArgs::augment_args(Command::new("companion")));
+
+	let matches = command.clone().get_matches_from(["companion"]);
+	let global_args = crate::global_args::GlobalArgs::from_arg_matches(&matches).unwrap();
+	assert_eq!(global_args.log_file, Some(PathBuf::from("logs/companion.log")));
+	assert_eq!(global_args.log_file_max_size, 1024);
+	assert_eq!(global_args.log_file_count, 2);
+
+	// the options given on the command line take precedence
+	let matches = command.get_matches_from(["companion", "--log-file", "other.log", "--log-file-count", "3"]);
+	let global_args = crate::global_args::GlobalArgs::from_arg_matches(&matches).unwrap();
+	assert_eq!(global_args.log_file, Some(PathBuf::from("other.log")));
+	assert_eq!(global_args.log_file_count, 3);
+
+	// without a configured file, the logs are only written to the console
+	let command = CompanionConfig::default().apply_defaults(crate::global_args::GlobalArgs::augment_args(Command::new("companion")));
+	let matches = command.get_matches_from(["companion"]);
+	assert_eq!(crate::global_args::GlobalArgs::from_arg_matches(&matches).unwrap().log_file, None);
+}
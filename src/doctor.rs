@@ -0,0 +1,342 @@
+use std::path::{Path, PathBuf};
+
+use alkali::{SODIUM_LIBRARY_VERSION_MAJOR, SODIUM_LIBRARY_VERSION_MINOR};
+use clap::Args;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Attribute, Cell, Row, Table};
+use log::{error, info, trace, warn};
+
+use crate::config::{self, CompanionConfig, DEFAULT_CONFIG_FILE};
+use crate::env::check::{check_values, resolve_node_env};
+use crate::env::env_file;
+use crate::env::schema::{EnvSchema, VariableCheckStatus};
+use crate::error::CompanionError;
+use crate::events;
+use crate::global_args;
+use crate::make::keys::verify::verify_values;
+use crate::structures::doctor_check::{DoctorCheck, DoctorReport, DoctorStatus};
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {}
+
+/// Load the project configuration, the one given with `--config` or `companion.toml` when it exists
+fn check_config(config: Option<&Path>) -> (DoctorCheck, CompanionConfig) {
+	let Some(path) = config::resolve_path(config) else {
+		return (
+			DoctorCheck::problem(
+				"Configuration",
+				DoctorStatus::Warn,
+				format!("{} does not exist, using the default settings", DEFAULT_CONFIG_FILE),
+				"Run `saas-template-companion init` to create it",
+			),
+			CompanionConfig::default(),
+		);
+	};
+
+	match CompanionConfig::load(&path) {
+		Ok(config) => (DoctorCheck::pass("Configuration", format!("{} is valid", path.display())), config),
+		Err(error) => (
+			DoctorCheck::problem("Configuration", DoctorStatus::Fail, format!("{:#}", error), format!("Fix or remove {}", path.display())),
+			CompanionConfig::default(),
+		),
+	}
+}
+
+/// Read the environment file, reporting the lines that cannot be parsed
+fn check_env_file(env: &Path, example: &Path) -> (DoctorCheck, Option<Vec<(String, String)>>) {
+	if !env.exists() {
+		return (
+			DoctorCheck::problem(
+				"Environment file",
+				DoctorStatus::Fail,
+				format!("{} does not exist", env.display()),
+				format!("Run `saas-template-companion init` or copy {} to {}", example.display(), env.display()),
+			),
+			None,
+		);
+	}
+
+	let lines = match env_file::read_lines(env) {
+		Ok(lines) => lines,
+		Err(error) => return (
+			DoctorCheck::problem("Environment file", DoctorStatus::Fail, format!("{:#}", error), format!("Check that {} is readable", env.display())),
+			None,
+		),
+	};
+
+	let unparsable: Vec<String> = lines.iter()
+	                                   .enumerate()
+	                                   .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+	                                   .filter(|(_, line)| env_file::parse_line(line).is_none())
+	                                   .map(|(index, _)| (index + 1).to_string())
+	                                   .collect();
+	let entries: Vec<(String, String)> = lines.iter()
+	                                          .filter_map(|line| env_file::parse_line(line))
+	                                          .map(|(name, value)| (name.to_owned(), value.into_owned()))
+	                                          .collect();
+
+	if !unparsable.is_empty() {
+		return (
+			DoctorCheck::problem(
+				"Environment file",
+				DoctorStatus::Fail,
+				format!("Line(s) {} of {} cannot be parsed", unparsable.join(", "), env.display()),
+				"Write each variable as a `NAME=\"value\"` line",
+			),
+			Some(entries),
+		);
+	}
+
+	(DoctorCheck::pass("Environment file", format!("{} variables in {}", entries.len(), env.display())), Some(entries))
+}
+
+/// Check the required variables are set and valid
+fn check_required_variables(schema_path: &Path, entries: &[(String, String)]) -> DoctorCheck {
+	if !schema_path.exists() {
+		return DoctorCheck::problem(
+			"Required variables",
+			DoctorStatus::Warn,
+			format!("{} does not exist, the variables cannot be checked", schema_path.display()),
+			format!("Declare the environment variables in {}", schema_path.display()),
+		);
+	}
+
+	let schema = match EnvSchema::load(schema_path) {
+		Ok(schema) => schema,
+		Err(error) => return DoctorCheck::problem(
+			"Required variables",
+			DoctorStatus::Fail,
+			format!("{:#}", error),
+			format!("Fix {}", schema_path.display()),
+		),
+	};
+
+	let node_env = resolve_node_env(None, entries);
+	let report = check_values(&schema, entries, &node_env);
+	if report.is_valid() {
+		return DoctorCheck::pass("Required variables", format!("All the variables match {} for {}", schema_path.display(), node_env));
+	}
+
+	let invalid: Vec<&str> = report.results
+	                               .iter()
+	                               .filter(|result| matches!(result.status(), VariableCheckStatus::Missing | VariableCheckStatus::Invalid))
+	                               .map(|result| result.name())
+	                               .collect();
+	DoctorCheck::problem(
+		"Required variables",
+		DoctorStatus::Fail,
+		format!("{} missing or invalid for {}", invalid.join(", "), node_env),
+		"Run `saas-template-companion env check` for the details",
+	)
+}
+
+/// Check the keys managed by `make keys`
+fn check_encryption_keys(entries: &[(String, String)]) -> DoctorCheck {
+	let values = entries.iter().cloned().collect();
+	let report = match verify_values(&values) {
+		Ok(report) => report,
+		Err(error) => return DoctorCheck::problem(
+			"Encryption keys",
+			DoctorStatus::Fail,
+			format!("{:#}", error),
+			"Run `saas-template-companion make keys`",
+		),
+	};
+
+	if report.is_valid() {
+		return DoctorCheck::pass("Encryption keys", "All the keys are valid");
+	}
+
+	let invalid: Vec<String> = report.results
+	                                 .iter()
+	                                 .filter(|result| !result.is_valid())
+	                                 .map(|result| format!("{} ({})", result.name(), result.details()))
+	                                 .collect();
+	DoctorCheck::problem(
+		"Encryption keys",
+		DoctorStatus::Fail,
+		invalid.join(", "),
+		"Run `saas-template-companion make keys --skip-existing`, or `make keys` to regenerate them all",
+	)
+}
+
+/// Report that the procedure signatures cannot be checked yet, so the gap shows up in the report
+fn check_signatures() -> DoctorCheck {
+	DoctorCheck::problem(
+		"Procedure signatures",
+		DoctorStatus::Warn,
+		"Signatures not checked: make signatures is not implemented",
+		"Nothing to do until `make signatures` is implemented",
+	)
+}
+
+/// Check the cryptographic library can be used
+fn check_sodium() -> DoctorCheck {
+	match alkali::require_init() {
+		Ok(_) => DoctorCheck::pass(
+			"libsodium",
+			format!("Sodium v{}.{}", SODIUM_LIBRARY_VERSION_MAJOR, SODIUM_LIBRARY_VERSION_MINOR),
+		),
+		Err(error) => DoctorCheck::problem(
+			"libsodium",
+			DoctorStatus::Fail,
+			format!("Sodium cannot be initialized: {}", error),
+			"Reinstall the companion",
+		),
+	}
+}
+
+/// List the files holding secrets: the environment file and its `.env.*` siblings, example files excluded
+fn secret_files(env: &Path) -> Vec<PathBuf> {
+	let mut files = vec![env.to_path_buf()];
+	let directory = env.parent().filter(|directory| !directory.as_os_str().is_empty()).unwrap_or(Path::new("."));
+
+	if let Ok(entries) = std::fs::read_dir(directory) {
+		let mut siblings: Vec<PathBuf> = entries.filter_map(Result::ok)
+		                                        .map(|entry| entry.path())
+		                                        .filter(|path| {
+			                                        path.file_name()
+			                                            .and_then(|name| name.to_str())
+			                                            .is_some_and(|name| name.starts_with(".env.") && !name.ends_with(".example"))
+		                                        })
+		                                        .filter(|path| path.file_name() != env.file_name())
+		                                        .collect();
+		siblings.sort();
+		files.extend(siblings);
+	}
+
+	files.into_iter().filter(|path| path.is_file()).collect()
+}
+
+/// Check the secret files cannot be read by every user
+fn check_permissions(env: &Path) -> DoctorCheck {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+
+		let readable: Vec<String> = secret_files(env).iter()
+		                                             .filter(|path| path.metadata().is_ok_and(|metadata| metadata.permissions().mode() & 0o004 != 0))
+		                                             .map(|path| path.display().to_string())
+		                                             .collect();
+		if !readable.is_empty() {
+			return DoctorCheck::problem(
+				"Secret file permissions",
+				DoctorStatus::Warn,
+				format!("{} readable by every user", readable.join(", ")),
+				format!("Run `chmod 600 {}`", readable.join(" ")),
+			);
+		}
+
+		DoctorCheck::pass("Secret file permissions", "Secret files are not world readable")
+	}
+	#[cfg(not(unix))]
+	{
+		let _ = secret_files(env);
+		DoctorCheck::pass("Secret file permissions", "Not checked on this platform")
+	}
+}
+
+/// Run all the checks on the project in the current working directory
+pub fn diagnose(config: Option<&Path>) -> DoctorReport {
+	let mut report = DoctorReport::default();
+
+	let (check, config) = check_config(config);
+	report.checks.push(check);
+
+	let (check, entries) = check_env_file(&config.env, &config.example);
+	report.checks.push(check);
+
+	match entries {
+		Some(entries) => {
+			report.checks.push(check_required_variables(&config.schema, &entries));
+			report.checks.push(check_encryption_keys(&entries));
+		}
+		None => {
+			for name in ["Required variables", "Encryption keys"] {
+				report.checks.push(DoctorCheck::problem(
+					name,
+					DoctorStatus::Fail,
+					"Skipped, the environment file cannot be read",
+					"Fix the environment file first",
+				));
+			}
+		}
+	}
+
+	report.checks.push(check_signatures());
+	report.checks.push(check_sodium());
+	report.checks.push(check_permissions(&config.env));
+
+	report
+}
+
+/// Display the doctor checks in a table
+fn display_doctor_table(report: &DoctorReport) {
+	let mut table = Table::new();
+	table.load_preset(UTF8_FULL)
+	     .set_header(vec![
+		     Cell::new("Check").add_attribute(Attribute::Bold),
+		     Cell::new("Status").add_attribute(Attribute::Bold),
+		     Cell::new("Details").add_attribute(Attribute::Bold),
+		     Cell::new("Hint").add_attribute(Attribute::Bold),
+	     ])
+	     .add_rows(
+		     report.checks
+		           .iter()
+		           .map(|check| Row::from(vec![
+			           check.name().to_owned(),
+			           format!("{:?}", check.status()),
+			           check.details().to_owned(),
+			           check.hint().unwrap_or_default().to_owned(),
+		           ]))
+	     );
+
+	println!("{table}");
+}
+
+/// Diagnose the local project setup, failing if any check fails
+pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &DoctorArgs, config: Option<&Path>) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	trace!("{:?}", arguments);
+
+	let report = diagnose(config);
+
+	if !global_arguments.json {
+		display_doctor_table(&report);
+	} else {
+		events::insert_json("doctor", &report)?;
+	}
+
+	let (passed, warnings, failures) = (
+		report.count(DoctorStatus::Pass),
+		report.count(DoctorStatus::Warn),
+		report.count(DoctorStatus::Fail),
+	);
+	if failures > 0 {
+		error!("{} passed, {} warnings, {} failed", passed, warnings, failures);
+		anyhow::bail!(CompanionError::Validation(format!("{} doctor checks failed", failures)));
+	} else if warnings > 0 {
+		warn!("{} passed, {} warnings, {} failed", passed, warnings, failures);
+	} else {
+		info!("{} passed, {} warnings, {} failed", passed, warnings, failures);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_check_environment_files() {
+	use assert_fs::prelude::*;
+
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("# comment\nPORT=3000\nnot a variable\n").unwrap();
+
+	let (check, entries) = check_env_file(env.path(), Path::new(".env.example"));
+	assert_eq!(check.status(), DoctorStatus::Fail);
+	assert!(check.details().starts_with("Line(s) 3 of"));
+	assert_eq!(entries.unwrap(), [("PORT".to_owned(), "3000".to_owned())]);
+
+	let check = check_encryption_keys(&[]);
+	assert_eq!(check.status(), DoctorStatus::Fail);
+	assert!(check.details().contains("NEXTAUTH_SECRET (Variable is not defined)"));
+}
@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand};
+use log::trace;
+
+use crate::global_args;
+
+pub mod check;
+pub mod encryption;
+pub mod env_file;
+pub mod schema;
+pub mod sealing;
+pub mod sync;
+mod table;
+pub mod variables;
+
+#[derive(Subcommand, Debug)]
+enum EnvSubCommand {
+	/// Encrypt an environment file (or some of its values) with a passphrase
+	#[command()]
+	Encrypt(encryption::EncryptArgs),
+
+	/// Decrypt a passphrase encrypted environment file
+	#[command()]
+	Decrypt(encryption::DecryptArgs),
+
+	/// Seal a value to the project asymmetric encryption public key
+	#[command()]
+	Seal(sealing::SealArgs),
+
+	/// Unseal values using the project asymmetric encryption private key
+	#[command()]
+	Unseal(sealing::UnsealArgs),
+
+	/// Set one or more variables
+	#[command()]
+	Set(variables::SetArgs),
+
+	/// Print the value of a variable
+	#[command()]
+	Get(variables::GetArgs),
+
+	/// Remove one or more variables
+	#[command()]
+	Unset(variables::UnsetArgs),
+
+	/// List all the variables, masking their values
+	#[command()]
+	List(variables::ListArgs),
+
+	/// Compare the environment file against its example
+	#[command()]
+	Diff(sync::DiffArgs),
+
+	/// Add the variables missing from the environment file using the example defaults
+	#[command()]
+	Sync(sync::SyncArgs),
+
+	/// Validate the environment file against its schema
+	#[command()]
+	Check(check::CheckArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct EnvArgs {
+	#[command(subcommand)]
+	command: EnvSubCommand,
+}
+
+impl EnvArgs {
+	/// Files the command reads or writes
+	fn files(&self) -> Vec<&Path> {
+		match &self.command {
+			EnvSubCommand::Encrypt(options) => vec![&options.env, &options.output],
+			EnvSubCommand::Decrypt(options) => vec![&options.input, &options.output],
+			EnvSubCommand::Seal(options) => [Some(&options.env), options.key_file.as_ref()].into_iter().flatten().collect(),
+			EnvSubCommand::Unseal(options) => [Some(&options.env), options.key_file.as_ref()].into_iter().flatten().collect(),
+			EnvSubCommand::Set(options) => vec![&options.env],
+			EnvSubCommand::Get(options) => vec![&options.env],
+			EnvSubCommand::Unset(options) => vec![&options.env],
+			EnvSubCommand::List(options) => vec![&options.env],
+			EnvSubCommand::Diff(options) => vec![&options.env, &options.example],
+			EnvSubCommand::Sync(options) => vec![&options.env, &options.example],
+			EnvSubCommand::Check(options) => vec![&options.env],
+		}.into_iter().map(PathBuf::as_path).collect()
+	}
+
+	/// Whether stdout is reserved for the environment file, when one of the files is `-`
+	pub fn writes_data_to_stdout(&self) -> bool {
+		self.files().into_iter().any(env_file::is_stdio)
+	}
+}
+
+pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &EnvArgs) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	// the arguments are not traced, they hold the values to set or seal in clear
+
+	match &arguments.command {
+		EnvSubCommand::Encrypt(options) => {
+			encryption::encrypt(global_arguments, options)
+		}
+		EnvSubCommand::Decrypt(options) => {
+			encryption::decrypt(global_arguments, options)
+		}
+		EnvSubCommand::Seal(options) => {
+			sealing::seal(global_arguments, options)
+		}
+		EnvSubCommand::Unseal(options) => {
+			sealing::unseal(global_arguments, options)
+		}
+		EnvSubCommand::Set(options) => {
+			variables::set(global_arguments, options)
+		}
+		EnvSubCommand::Get(options) => {
+			variables::get(global_arguments, options)
+		}
+		EnvSubCommand::Unset(options) => {
+			variables::unset(global_arguments, options)
+		}
+		EnvSubCommand::List(options) => {
+			variables::list(global_arguments, options)
+		}
+		EnvSubCommand::Diff(options) => {
+			sync::diff(global_arguments, options)
+		}
+		EnvSubCommand::Sync(options) => {
+			sync::sync(global_arguments, options)
+		}
+		EnvSubCommand::Check(options) => {
+			check::check(global_arguments, options)
+		}
+	}
+}
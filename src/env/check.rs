@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use log::{error, info};
+
+use crate::env::schema::{validate_value, EnvCheckReport, EnvSchema, VariableCheck, VariableCheckStatus};
+use crate::env::{env_file, table};
+use crate::error::CompanionError;
+use crate::events;
+use crate::global_args;
+
+/// Name of the variable selecting the environment the required variables are resolved for
+const NODE_ENV: &str = "NODE_ENV";
+
+/// Environment used when `NODE_ENV` is defined nowhere
+const DEFAULT_NODE_ENV: &str = "development";
+
+/// Prefixes of the values encrypted or sealed by the companion, which cannot be type checked
+const PROTECTED_VALUE_PREFIXES: [&str; 2] = ["enc:", "sealed:"];
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+	/// Environment file to check, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// Schema file declaring the variables, read as JSON for a `.json` extension and as TOML otherwise
+	#[arg(long, short, default_value = "env.schema.toml")]
+	schema: PathBuf,
+
+	/// Environment to resolve the required variables for, defaults to `NODE_ENV` from the file, then from the process
+	#[arg(long)]
+	node_env: Option<String>,
+}
+
+/// Resolve the environment the required variables are checked for, from the given value, then
+/// `NODE_ENV` from the file, then from the process
+pub fn resolve_node_env(node_env: Option<String>, values: &[(String, String)]) -> String {
+	node_env.or_else(|| values.iter().find(|(name, _)| name == NODE_ENV).map(|(_, value)| value.clone()))
+	        .or_else(|| std::env::var(NODE_ENV).ok())
+	        .filter(|value| !value.is_empty())
+	        .unwrap_or_else(|| DEFAULT_NODE_ENV.to_owned())
+}
+
+/// Check the values of an environment file against the schema
+pub fn check_values(schema: &EnvSchema, values: &[(String, String)], node_env: &str) -> EnvCheckReport {
+	let mut results = Vec::new();
+
+	for (name, declaration) in &schema.variables {
+		let value = values.iter()
+		                  .find(|(variable, _)| variable == name)
+		                  .map(|(_, value)| value.as_str())
+		                  .filter(|value| !env_file::is_placeholder(Some(value)));
+
+		let result = match value {
+			None if declaration.required.applies_to(node_env) => {
+				VariableCheck::new(name, VariableCheckStatus::Missing, format!("required in {}", node_env))
+			}
+			None => VariableCheck::new(name, VariableCheckStatus::Unset, "optional"),
+			Some(value) if PROTECTED_VALUE_PREFIXES.iter().any(|prefix| value.starts_with(prefix)) => {
+				VariableCheck::new(name, VariableCheckStatus::Valid, "protected value, type not checked")
+			}
+			Some(value) => match validate_value(&declaration.kind, value) {
+				Ok(()) => VariableCheck::new(name, VariableCheckStatus::Valid, declaration.description.clone().unwrap_or_default()),
+				Err(reason) => VariableCheck::new(name, VariableCheckStatus::Invalid, reason),
+			},
+		};
+		results.push(result);
+	}
+
+	for (name, _) in values.iter().filter(|(name, _)| !schema.variables.contains_key(name)) {
+		results.push(VariableCheck::new(name, VariableCheckStatus::Undeclared, "not declared in the schema"));
+	}
+
+	EnvCheckReport {
+		node_env: node_env.to_owned(),
+		results,
+	}
+}
+
+/// Validate an environment file against the schema
+pub fn check(global_arguments: &global_args::GlobalArgs, arguments: &CheckArgs) -> anyhow::Result<()> {
+	let schema = EnvSchema::load(&arguments.schema)?;
+	let values = env_file::read_entries(&arguments.env)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.env.display()))?;
+
+	let node_env = resolve_node_env(arguments.node_env.clone(), &values);
+
+	let report = check_values(&schema, &values, &node_env);
+
+	if !global_arguments.json {
+		table::display_check_table(&report);
+	} else {
+		events::insert_json("check", &report)?;
+	}
+
+	if !report.is_valid() {
+		error!("{} does not match {} for {}", arguments.env.display(), arguments.schema.display(), node_env);
+		anyhow::bail!(CompanionError::Validation("Environment file check failed".to_owned()));
+	}
+
+	info!("{} matches {} for {}", arguments.env.display(), arguments.schema.display(), node_env);
+
+	Ok(())
+}
+
+#[test]
+fn can_check_required_variables_per_environment() {
+	let schema: EnvSchema = toml::from_str(r#"
+		[variables.DATABASE_URL]
+		type = "url"
+		required = true
+
+		[variables.GITHUB_SECRET]
+		type = "string"
+		required = ["production"]
+	"#).unwrap();
+	let values = vec![
+		("DATABASE_URL".to_owned(), "postgres://localhost/db".to_owned()),
+		("LOCAL_ONLY".to_owned(), "1".to_owned()),
+	];
+
+	let report = check_values(&schema, &values, "development");
+	assert!(report.is_valid());
+	assert_eq!(report.results[1].status(), VariableCheckStatus::Unset);
+	assert_eq!(report.results[2].status(), VariableCheckStatus::Undeclared);
+
+	let report = check_values(&schema, &values, "production");
+	assert!(!report.is_valid());
+	assert_eq!(report.results[1].status(), VariableCheckStatus::Missing);
+}
+
+#[test]
+fn can_report_invalid_and_placeholder_values() {
+	let schema: EnvSchema = toml::from_str(r#"
+		[variables.DATABASE_URL]
+		type = "url"
+		required = true
+
+		[variables.NEXTAUTH_SECRET]
+		type = "base64-key"
+		length = 32
+		required = true
+	"#).unwrap();
+	let values = vec![
+		("DATABASE_URL".to_owned(), "localhost:5432/db".to_owned()),
+		("NEXTAUTH_SECRET".to_owned(), "<changeme>".to_owned()),
+	];
+
+	let report = check_values(&schema, &values, "development");
+	assert_eq!(report.results[0].status(), VariableCheckStatus::Invalid);
+	assert_eq!(report.results[1].status(), VariableCheckStatus::Missing);
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use alkali::hash::pbkdf::argon2id;
+use alkali::mem::FullAccess;
+use alkali::symmetric::aead::xchacha20poly1305_ietf as aead;
+use anyhow::Context;
+use clap::Args;
+use log::{debug, info, warn};
+
+use crate::env::env_file;
+use crate::error::CompanionError;
+use crate::global_args;
+use crate::helpers::{base64_url, base64_url_decode};
+use crate::redaction;
+
+/// First line of an armored encrypted environment file
+const ARMOR_HEADER: &str = "-----BEGIN SAAS TEMPLATE COMPANION ENCRYPTED ENV-----";
+
+/// Last line of an armored encrypted environment file
+const ARMOR_FOOTER: &str = "-----END SAAS TEMPLATE COMPANION ENCRYPTED ENV-----";
+
+/// Maximum length of the armored ciphertext lines
+const ARMOR_LINE_LENGTH: usize = 64;
+
+/// Version of the encryption format, prepended to every encrypted payload
+const FORMAT_VERSION: &str = "v1";
+
+/// Prefix of the values encrypted one by one
+const VALUE_PREFIX: &str = "enc:";
+
+#[derive(Args, Debug)]
+pub struct EncryptArgs {
+	/// File to encrypt, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// File to write the encrypted content to
+	#[arg(long, short, default_value = ".env.enc")]
+	pub(super) output: PathBuf,
+
+	/// Only encrypt the values of the given variables, leaving the rest of the file readable
+	#[arg(long, value_delimiter = ',')]
+	only: Vec<String>,
+
+	/// Environment variable holding the passphrase
+	#[arg(long, default_value = "SAAS_COMPANION_PASSPHRASE")]
+	passphrase_env: String,
+}
+
+#[derive(Args, Debug)]
+pub struct DecryptArgs {
+	/// Encrypted file to decrypt, starting from the current working directory
+	#[arg(long, short, default_value = ".env.enc")]
+	pub(super) input: PathBuf,
+
+	/// File to write the decrypted content to
+	#[arg(long, short, default_value = ".env")]
+	pub(super) output: PathBuf,
+
+	/// Overwrite the output file if it already exists
+	#[arg(long)]
+	force: bool,
+
+	/// Environment variable holding the passphrase
+	#[arg(long, default_value = "SAAS_COMPANION_PASSPHRASE")]
+	passphrase_env: String,
+}
+
+/// Load the passphrase from the process environment variable with the given name
+fn load_passphrase(passphrase_env: &str) -> anyhow::Result<String> {
+	let passphrase = std::env::var(passphrase_env)
+		.with_context(|| format!("Cannot read the passphrase from the {} environment variable", passphrase_env))?;
+	redaction::register_secret(&passphrase);
+
+	Ok(passphrase)
+}
+
+/// Derive the encryption key from the passphrase using Argon2id
+fn derive_key(passphrase: &str, salt: &argon2id::Salt) -> anyhow::Result<aead::Key<FullAccess>> {
+	let mut key = aead::Key::new_empty()
+		.with_context(|| "Something went wrong while allocating the encryption key")?;
+
+	argon2id::derive_key(
+		passphrase.as_bytes(),
+		salt,
+		argon2id::OPS_LIMIT_INTERACTIVE,
+		argon2id::MEM_LIMIT_INTERACTIVE,
+		&mut key[..],
+	).with_context(|| "Something went wrong while deriving the encryption key from the passphrase")?;
+
+	Ok(key)
+}
+
+/// Encrypt the plaintext, returning the encoded `<version>:<salt>:<nonce>:<ciphertext>` payload
+fn seal(plaintext: &[u8], additional_data: &[u8], salt: &argon2id::Salt, key: &aead::Key<FullAccess>) -> anyhow::Result<String> {
+	let mut ciphertext = vec![0u8; plaintext.len() + aead::MAC_LENGTH];
+	let (_, nonce) = aead::encrypt(plaintext, Some(additional_data), key, None, &mut ciphertext)
+		.with_context(|| "Something went wrong while encrypting")?;
+
+	Ok(format!(
+		"{}:{}:{}:{}",
+		FORMAT_VERSION,
+		base64_url(salt).with_context(|| "Something went wrong while encoding the salt")?,
+		base64_url(&nonce).with_context(|| "Something went wrong while encoding the nonce")?,
+		base64_url(&ciphertext).with_context(|| "Something went wrong while encoding the ciphertext")?,
+	))
+}
+
+/// Decrypt an encoded payload, deriving (and caching) the key for its salt
+fn open(
+	payload: &str,
+	additional_data: &[u8],
+	passphrase: &str,
+	keys: &mut HashMap<String, aead::Key<FullAccess>>,
+) -> anyhow::Result<Vec<u8>> {
+	let parts: Vec<&str> = payload.split(':').collect();
+	let (salt, nonce, ciphertext) = match parts.as_slice() {
+		[version, salt, nonce, ciphertext] if *version == FORMAT_VERSION => (*salt, *nonce, *ciphertext),
+		_ => anyhow::bail!(CompanionError::Parse("Unsupported encrypted payload format".to_owned())),
+	};
+
+	if !keys.contains_key(salt) {
+		let decoded_salt = argon2id::Salt::try_from(
+			base64_url_decode(salt).with_context(|| "Something went wrong while decoding the salt")?.as_slice()
+		).map_err(|_| CompanionError::Parse("Invalid salt length".to_owned()))?;
+
+		keys.insert(salt.to_owned(), derive_key(passphrase, &decoded_salt)?);
+	}
+
+	let nonce = aead::Nonce::try_from(
+		base64_url_decode(nonce).with_context(|| "Something went wrong while decoding the nonce")?.as_slice()
+	).map_err(|_| CompanionError::Parse("Invalid nonce length".to_owned()))?;
+	let ciphertext = base64_url_decode(ciphertext).with_context(|| "Something went wrong while decoding the ciphertext")?;
+
+	let mut plaintext = vec![0u8; ciphertext.len().saturating_sub(aead::MAC_LENGTH)];
+	aead::decrypt(&ciphertext, Some(additional_data), &keys[salt], &nonce, &mut plaintext)
+		.with_context(|| "Decryption failed, is the passphrase correct?")?;
+
+	Ok(plaintext)
+}
+
+/// Wrap the payload in the armor block
+fn armor(payload: &str) -> String {
+	let (header, ciphertext) = payload.rsplit_once(':').unwrap_or(("", payload));
+
+	let mut armored = format!("{}\n{}\n", ARMOR_HEADER, header);
+	for chunk in ciphertext.as_bytes().chunks(ARMOR_LINE_LENGTH) {
+		armored.push_str(&String::from_utf8_lossy(chunk));
+		armored.push('\n');
+	}
+	armored.push_str(ARMOR_FOOTER);
+	armored.push('\n');
+
+	armored
+}
+
+/// Extract the payload from the armor block
+fn dearmor(content: &str) -> anyhow::Result<String> {
+	let mut lines = content.lines()
+	                       .map(str::trim)
+	                       .skip_while(|line| *line != ARMOR_HEADER)
+	                       .skip(1)
+	                       .take_while(|line| *line != ARMOR_FOOTER);
+
+	let header = lines.next().ok_or_else(|| CompanionError::Parse("Missing encryption header".to_owned()))?;
+	let ciphertext: String = lines.collect();
+
+	Ok(format!("{}:{}", header, ciphertext))
+}
+
+/// Encrypt an environment file, either as a whole or value by value
+pub fn encrypt(global_arguments: &global_args::GlobalArgs, arguments: &EncryptArgs) -> anyhow::Result<()> {
+	info!("Encrypting {}", arguments.env.display());
+	env_file::ensure_exists(&arguments.env)?;
+
+	let passphrase = load_passphrase(&arguments.passphrase_env)?;
+	let salt = argon2id::generate_salt().with_context(|| "Something went wrong while generating the salt")?;
+	let key = derive_key(&passphrase, &salt)?;
+
+	let lines = env_file::read_lines(&arguments.env)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.env.display()))?;
+
+	let content = if arguments.only.is_empty() {
+		armor(&seal(lines.concat().as_bytes(), ARMOR_HEADER.as_bytes(), &salt, &key)?)
+	} else {
+		let mut content = String::new();
+
+		for line in lines {
+			match env_file::parse_line(&line) {
+				Some((name, value)) if arguments.only.iter().any(|only| only == name) => {
+					debug!("Encrypting {}", name);
+					let payload = seal(value.as_bytes(), name.as_bytes(), &salt, &key)?;
+					content.push_str(&env_file::format_line(name, &format!("{}{}", VALUE_PREFIX, payload)));
+				}
+				_ => content.push_str(&line),
+			}
+		}
+
+		content
+	};
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::write_content(&arguments.output, &content)
+		.with_context(|| format!("Something went wrong while writing {}", arguments.output.display()))?;
+	info!("Encrypted content written to {}", arguments.output.display());
+
+	Ok(())
+}
+
+/// Decrypt an environment file encrypted with `env encrypt`
+pub fn decrypt(global_arguments: &global_args::GlobalArgs, arguments: &DecryptArgs) -> anyhow::Result<()> {
+	info!("Decrypting {}", arguments.input.display());
+	env_file::ensure_exists(&arguments.input)?;
+
+	if arguments.output.exists() && !arguments.force {
+		anyhow::bail!(CompanionError::Io(std::io::Error::new(
+			std::io::ErrorKind::AlreadyExists,
+			format!("{} already exists, run with --force to overwrite it", arguments.output.display()),
+		)));
+	}
+
+	let passphrase = load_passphrase(&arguments.passphrase_env)?;
+	let mut keys = HashMap::new();
+
+	let lines = env_file::read_lines(&arguments.input)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.input.display()))?;
+	let encrypted_content = lines.concat();
+
+	let content = if encrypted_content.trim_start().starts_with(ARMOR_HEADER) {
+		let plaintext = open(&dearmor(&encrypted_content)?, ARMOR_HEADER.as_bytes(), &passphrase, &mut keys)?;
+		String::from_utf8(plaintext).with_context(|| "Decrypted content is not valid UTF-8")?
+	} else {
+		let mut content = String::new();
+
+		for line in lines {
+			match env_file::parse_line(&line) {
+				Some((name, value)) if value.starts_with(VALUE_PREFIX) => {
+					debug!("Decrypting {}", name);
+					let plaintext = open(&value[VALUE_PREFIX.len()..], name.as_bytes(), &passphrase, &mut keys)
+						.with_context(|| format!("Something went wrong while decrypting {}", name))?;
+					let value = String::from_utf8(plaintext).with_context(|| format!("Decrypted {} is not valid UTF-8", name))?;
+					content.push_str(&env_file::format_line(name, &value));
+				}
+				_ => content.push_str(&line),
+			}
+		}
+
+		content
+	};
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::write_content(&arguments.output, &content)
+		.with_context(|| format!("Something went wrong while writing {}", arguments.output.display()))?;
+	info!("Decrypted content written to {}", arguments.output.display());
+
+	Ok(())
+}
+
+#[test]
+fn can_armor_and_dearmor_payloads() {
+	let payload = format!("{}:salt:nonce:{}", FORMAT_VERSION, "c".repeat(150));
+
+	assert_eq!(dearmor(&armor(&payload)).unwrap(), payload);
+}
@@ -0,0 +1,472 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use log::{debug, info, warn};
+
+use crate::error::CompanionError;
+use crate::structures::file_mode::FileMode;
+use crate::structures::stream_reader::StreamReader;
+use crate::structures::stream_writer::StreamWriter;
+
+/// Path standing for stdin when reading the env file and stdout when writing it
+pub const STDIO_PATH: &str = "-";
+
+/// Lines read from stdin, which can only be consumed once
+static STDIN_LINES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Values commonly used in env examples to mark a variable that must be filled in
+const PLACEHOLDER_VALUES: [&str; 8] = [
+	"changeme",
+	"change-me",
+	"change_me",
+	"replace-me",
+	"replace_me",
+	"placeholder",
+	"todo",
+	"xxx",
+];
+
+/// Split an env file line into the variable name and its unquoted value, comments and blank lines
+/// are ignored, escape sequences are only interpreted in double quoted values
+pub fn parse_line(line: &str) -> Option<(&str, Cow<'_, str>)> {
+	let line = line.trim();
+	if line.is_empty() || line.starts_with('#') {
+		return None;
+	}
+
+	let line = line.strip_prefix("export ").unwrap_or(line);
+	let (name, value) = line.split_once('=')?;
+	let value = value.trim();
+
+	let value = if let Some(value) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+		unescape(value)
+	} else {
+		Cow::Borrowed(value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')).unwrap_or(value))
+	};
+
+	Some((name.trim(), value))
+}
+
+/// Interpret the `\\`, `\"` and `\n` escape sequences of a double quoted value, any other
+/// backslash is kept as is
+fn unescape(value: &str) -> Cow<'_, str> {
+	if !value.contains('\\') {
+		return Cow::Borrowed(value);
+	}
+
+	let mut unescaped = String::with_capacity(value.len());
+	let mut characters = value.chars();
+	while let Some(character) = characters.next() {
+		if character != '\\' {
+			unescaped.push(character);
+			continue;
+		}
+
+		match characters.next() {
+			Some('n') => unescaped.push('\n'),
+			Some(escaped @ ('"' | '\\')) => unescaped.push(escaped),
+			Some(other) => {
+				unescaped.push('\\');
+				unescaped.push(other);
+			}
+			None => unescaped.push('\\'),
+		}
+	}
+
+	Cow::Owned(unescaped)
+}
+
+/// Check whether the value is missing, empty or a well known placeholder
+pub fn is_placeholder(value: Option<&str>) -> bool {
+	match value.map(str::trim) {
+		None | Some("") => true,
+		Some(value) => {
+			(value.starts_with('<') && value.ends_with('>')) ||
+				PLACEHOLDER_VALUES.contains(&value.to_lowercase().as_str())
+		}
+	}
+}
+
+/// Format a variable as a double quoted env file line, escaping the characters that would end the
+/// value early
+pub fn format_line(name: &str, value: &str) -> String {
+	let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+	format!("{}=\"{}\"\n", name, value)
+}
+
+/// Check whether a variable name can be written to and read back from an env file, i.e. matches
+/// `[A-Za-z_][A-Za-z0-9_]*`
+pub fn is_valid_name(name: &str) -> bool {
+	let mut characters = name.chars();
+
+	characters.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_') &&
+		characters.all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+/// Check whether the env file is read from stdin and written to stdout
+pub fn is_stdio(env: &Path) -> bool {
+	env.as_os_str() == STDIO_PATH
+}
+
+/// Resolve env file paths and glob patterns (e.g. `apps/*/.env`) into a list of unique paths, in
+/// the given order, plain paths are kept even if they do not exist yet
+pub fn resolve_paths(patterns: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+	let mut paths: Vec<PathBuf> = Vec::new();
+
+	for pattern in patterns {
+		let pattern = pattern.to_str().ok_or(anyhow::anyhow!("Cannot convert environment file path to string"))?;
+
+		let matches = if pattern.contains(['*', '?', '[']) {
+			let mut matches = glob::glob(pattern)
+				.with_context(|| format!("Invalid environment file pattern {}", pattern))?
+				.collect::<Result<Vec<_>, _>>()
+				.with_context(|| format!("Something went wrong while resolving {}", pattern))?;
+			matches.retain(|path| path.is_file());
+
+			if matches.is_empty() {
+				warn!("No environment file matches {}", pattern);
+			}
+			matches
+		} else {
+			vec![PathBuf::from(pattern)]
+		};
+
+		for path in matches {
+			if !paths.contains(&path) {
+				paths.push(path);
+			}
+		}
+	}
+
+	if paths.is_empty() {
+		anyhow::bail!(CompanionError::Config("No environment file to work on".to_owned()));
+	}
+
+	Ok(paths)
+}
+
+/// Read all the lines from the source
+pub fn read_lines_from<R: Read>(stream_reader: &mut StreamReader<R>) -> anyhow::Result<Vec<String>> {
+	let mut lines = Vec::new();
+
+	loop {
+		let line = stream_reader.read_line()
+		                        .with_context(|| "Something went wrong while reading a new file line")?;
+
+		if line.eof() {
+			debug!("EOF reached at {}", stream_reader.name());
+			break;
+		}
+
+		lines.push(line.line().to_owned());
+	}
+
+	Ok(lines)
+}
+
+/// Fail when the env file does not exist, for the commands which would otherwise read a missing
+/// file as an empty one, `-` always exists
+pub fn ensure_exists(env: &Path) -> anyhow::Result<()> {
+	if is_stdio(env) || env.exists() {
+		return Ok(());
+	}
+
+	Err(CompanionError::Io(std::io::Error::new(
+		std::io::ErrorKind::NotFound,
+		format!("{} does not exist", env.display()),
+	)).into())
+}
+
+/// Read all the lines of the env file, a missing file has no lines and `-` reads stdin, only
+/// consumed on the first call
+pub fn read_lines(env: &Path) -> anyhow::Result<Vec<String>> {
+	if is_stdio(env) {
+		if let Some(lines) = STDIN_LINES.get() {
+			return Ok(lines.clone());
+		}
+
+		let lines = read_lines_from(&mut StreamReader::stdin())?;
+		return Ok(STDIN_LINES.get_or_init(|| lines).clone());
+	}
+
+	if !env.exists() {
+		debug!("{} does not exist, no line loaded", env.display());
+		return Ok(Vec::new());
+	}
+
+	let env = env.to_str().ok_or(anyhow::anyhow!("Cannot convert environment file path to string"))?;
+
+	let mut stream_reader = StreamReader::new(
+		env,
+		FileMode::builder().read().build()?,
+	).with_context(|| format!("Something went wrong while opening stream reader to {}", env))?;
+
+	read_lines_from(&mut stream_reader)
+}
+
+/// Read all the variables defined in the env file in order of definition, a missing file has no
+/// variables
+pub fn read_entries(env: &Path) -> anyhow::Result<Vec<(String, String)>> {
+	Ok(
+		read_lines(env)?
+			.iter()
+			.filter_map(|line| parse_line(line))
+			.map(|(name, value)| (name.to_owned(), value.into_owned()))
+			.collect()
+	)
+}
+
+/// Read all the variables defined in the env file, a missing file has no variables
+pub fn read_values(env: &Path) -> anyhow::Result<HashMap<String, String>> {
+	Ok(read_entries(env)?.into_iter().collect())
+}
+
+/// Set the given variables in the env file lines, defined variables are updated in place while
+/// missing ones are appended, any other line is preserved as is
+pub fn update_lines(lines: &[String], values: &[(String, String)]) -> String {
+	let mut content = String::new();
+	let mut written = HashSet::new();
+
+	for line in lines {
+		let value = parse_line(line).and_then(|(name, _)| values.iter().find(|(variable, _)| variable == name));
+
+		match value {
+			Some((name, value)) if !written.contains(name) => {
+				content.push_str(&format_line(name, value));
+				written.insert(name);
+			}
+			_ => content.push_str(line),
+		}
+	}
+
+	if !content.is_empty() && !content.ends_with('\n') {
+		content.push('\n');
+	}
+
+	for (name, value) in values.iter().filter(|(name, _)| !written.contains(name)) {
+		content.push_str(&format_line(name, value));
+	}
+
+	content
+}
+
+/// Set the given variables in the env file, see [`update_lines`]
+pub fn update_values(env: &Path, values: &[(String, String)]) -> anyhow::Result<()> {
+	write_content(env, &update_lines(&read_lines(env)?, values))
+}
+
+/// Remove the given variables from the env file, any other line is preserved as is
+pub fn remove_values(env: &Path, names: &[String]) -> anyhow::Result<()> {
+	let content: String = read_lines(env)?
+		.into_iter()
+		.filter(|line| !parse_line(line).is_some_and(|(name, _)| names.iter().any(|variable| variable == name)))
+		.collect();
+
+	write_content(env, &content)
+}
+
+/// Write the whole content to the destination
+pub fn write_to<W: Write>(stream_writer: &mut StreamWriter<W>, content: &str) -> anyhow::Result<()> {
+	stream_writer.write_all(content.as_bytes())
+}
+
+/// Replace the whole content of a file opened with the given mode, `-` writes to stdout
+fn write_with_mode(env: &Path, content: &str, mode: FileMode) -> anyhow::Result<()> {
+	if is_stdio(env) {
+		return write_to(&mut StreamWriter::stdout(), content);
+	}
+
+	let env = env.to_str().ok_or(anyhow::anyhow!("Cannot convert environment file path to string"))?;
+
+	let mut stream_writer = StreamWriter::new(env, mode)
+		.with_context(|| format!("Something went wrong while opening stream writer to {}", env))?;
+
+	write_to(&mut stream_writer, content)
+}
+
+/// Replace the whole content of the env file, creating it readable by its owner only if needed
+pub fn write_content(env: &Path, content: &str) -> anyhow::Result<()> {
+	write_with_mode(env, content, FileMode::builder().write().create().truncate().private().build()?)
+}
+
+/// Replace the whole content of a file holding no secret (e.g. generated declarations), creating
+/// it with the default permissions if needed
+pub fn write_public_content(path: &Path, content: &str) -> anyhow::Result<()> {
+	write_with_mode(path, content, FileMode::builder().write().create().truncate().build()?)
+}
+
+/// Make sure a file holding secrets can be safely written: group or world readable files are
+/// restricted to their owner when `fix_permissions` is set, reported otherwise, and the parent
+/// directory must belong to the current user when `require_owned_directory` is set
+pub fn protect_secret_file(env: &Path, fix_permissions: bool, require_owned_directory: bool) -> anyhow::Result<()> {
+	if is_stdio(env) {
+		// the stream permissions belong to whoever set up the pipe
+		return Ok(());
+	}
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+		if require_owned_directory {
+			let directory = env.parent().filter(|directory| !directory.as_os_str().is_empty()).unwrap_or(Path::new("."));
+			let owner = directory.metadata()
+			                     .with_context(|| format!("Something went wrong while reading the owner of {}", directory.display()))?
+			                     .uid();
+
+			// SAFETY: geteuid cannot fail and has no side effect
+			if owner != unsafe { libc::geteuid() } {
+				anyhow::bail!(CompanionError::Io(std::io::Error::new(
+					std::io::ErrorKind::PermissionDenied,
+					format!("Refusing to write {}, {} is not owned by the current user", env.display(), directory.display()),
+				)));
+			}
+		}
+
+		let Ok(metadata) = env.metadata() else {
+			// new files are created private
+			return Ok(());
+		};
+
+		let mode = metadata.permissions().mode() & 0o777;
+		if mode & 0o077 != 0 {
+			if fix_permissions {
+				std::fs::set_permissions(env, std::fs::Permissions::from_mode(0o600))
+					.with_context(|| format!("Something went wrong while restricting the permissions of {}", env.display()))?;
+				info!("Permissions of {} restricted from {:o} to 600", env.display(), mode);
+			} else {
+				warn!(
+					"{} is readable by other users ({:o}), run with --fix-permissions or `chmod 600 {}`",
+					env.display(),
+					mode,
+					env.display(),
+				);
+			}
+		}
+	}
+	#[cfg(not(unix))]
+	{
+		let _ = (env, fix_permissions, require_owned_directory);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_validate_names() {
+	assert!(is_valid_name("STRIPE_SECRET"));
+	assert!(is_valid_name("_PRIVATE2"));
+	assert!(!is_valid_name(""));
+	assert!(!is_valid_name("#X"));
+	assert!(!is_valid_name("BAD NAME"));
+	assert!(!is_valid_name("2FA_SECRET"));
+}
+
+#[test]
+fn can_parse_lines() {
+	assert_eq!(parse_line("NAME=value\n"), Some(("NAME", "value".into())));
+	assert_eq!(parse_line("NAME=\"quoted value\"\n"), Some(("NAME", "quoted value".into())));
+	assert_eq!(parse_line("export NAME='single'"), Some(("NAME", "single".into())));
+	assert_eq!(parse_line("NAME=a=b"), Some(("NAME", "a=b".into())));
+	assert_eq!(parse_line("NAME=\"C:\\path\""), Some(("NAME", "C:\\path".into())));
+	assert_eq!(parse_line("NAME='x\\\"y'"), Some(("NAME", "x\\\"y".into())));
+	assert_eq!(parse_line("# NAME=value"), None);
+	assert_eq!(parse_line("\n"), None);
+}
+
+#[test]
+fn can_format_and_parse_escaped_values() {
+	for value in ["x\"y", "back\\slash", "multi\nline", "\\\"", "plain"] {
+		let line = format_line("NAME", value);
+		assert_eq!(line.matches('\n').count(), 1);
+		assert_eq!(parse_line(&line), Some(("NAME", value.into())));
+	}
+}
+
+#[test]
+fn can_detect_placeholders() {
+	assert!(is_placeholder(None));
+	assert!(is_placeholder(Some("")));
+	assert!(is_placeholder(Some("CHANGEME")));
+	assert!(is_placeholder(Some("<your secret>")));
+	assert!(!is_placeholder(Some("c2VjcmV0")));
+}
+
+#[test]
+fn can_update_values_in_place() {
+	use assert_fs::prelude::*;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str("# comment\nFIRST=1\nSECOND=2").unwrap();
+
+	update_values(
+		file.path(),
+		&[("SECOND".to_owned(), "two".to_owned()), ("THIRD".to_owned(), "three".to_owned())],
+	).unwrap();
+
+	file.assert("# comment\nFIRST=1\nSECOND=\"two\"\nTHIRD=\"three\"\n");
+}
+
+#[test]
+fn can_update_lines_read_from_memory() {
+	let mut stream_reader = StreamReader::from_reader("buffer", "FIRST=1\nSECOND=2".as_bytes());
+	let lines = read_lines_from(&mut stream_reader).unwrap();
+
+	let mut stream_writer = StreamWriter::from_writer("buffer", Vec::new());
+	write_to(&mut stream_writer, &update_lines(&lines, &[("FIRST".to_owned(), "one".to_owned())])).unwrap();
+
+	assert_eq!(stream_writer.into_inner(), b"FIRST=\"one\"\nSECOND=2\n");
+}
+
+#[test]
+fn can_remove_values() {
+	use assert_fs::prelude::*;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str("# comment\nFIRST=1\nSECOND=2\n").unwrap();
+
+	remove_values(file.path(), &["FIRST".to_owned()]).unwrap();
+
+	file.assert("# comment\nSECOND=2\n");
+}
+
+#[test]
+fn can_resolve_paths() {
+	let directory = std::env::temp_dir().join(format!("saas-companion-resolve-{}", std::process::id()));
+	for app in ["admin", "web"] {
+		std::fs::create_dir_all(directory.join(app)).unwrap();
+		std::fs::write(directory.join(app).join(".env"), "").unwrap();
+	}
+
+	let pattern = directory.join("*").join(".env");
+	let missing = directory.join("worker").join(".env");
+	let paths = resolve_paths(&[pattern.clone(), missing.clone(), pattern]).unwrap();
+	assert_eq!(paths, vec![directory.join("admin").join(".env"), directory.join("web").join(".env"), missing]);
+
+	assert!(resolve_paths(&[directory.join("none").join("*.env")]).is_err());
+
+	std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn can_protect_secret_files() {
+	use std::os::unix::fs::PermissionsExt;
+
+	let directory = assert_fs::TempDir::new().unwrap();
+	let env = directory.path().join(".env");
+	let mode = |path: &Path| path.metadata().unwrap().permissions().mode() & 0o777;
+
+	write_content(&env, "SECRET=1\n").unwrap();
+	assert_eq!(mode(&env), 0o600);
+
+	std::fs::set_permissions(&env, std::fs::Permissions::from_mode(0o644)).unwrap();
+	protect_secret_file(&env, false, true).unwrap();
+	assert_eq!(mode(&env), 0o644);
+	protect_secret_file(&env, true, true).unwrap();
+	assert_eq!(mode(&env), 0o600);
+}
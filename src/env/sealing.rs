@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use alkali::asymmetric::seal;
+use anyhow::Context;
+use clap::Args;
+use log::{debug, info, warn};
+
+use crate::env::env_file;
+use crate::error::CompanionError;
+use crate::global_args;
+use crate::helpers::{base64_url, base64_url_decode};
+use crate::make::keys::constants;
+
+/// Prefix of the sealed values
+const SEALED_PREFIX: &str = "sealed:";
+
+#[derive(Args, Debug)]
+pub struct SealArgs {
+	/// Name of the environment variable to seal
+	name: String,
+
+	/// Value to seal, read from stdin if not provided
+	#[arg(long)]
+	value: Option<String>,
+
+	/// File to store the sealed value into, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// File to read the asymmetric encryption public key from, defaults to the environment file
+	#[arg(long)]
+	pub(super) key_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct UnsealArgs {
+	/// Names of the environment variables to unseal, all sealed values are unsealed if none is provided
+	names: Vec<String>,
+
+	/// File to read the sealed values from, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// File to read the asymmetric encryption private key from, defaults to the environment file
+	#[arg(long)]
+	pub(super) key_file: Option<PathBuf>,
+
+	/// Print the unsealed values instead of storing them in the environment file
+	#[arg(long)]
+	print: bool,
+}
+
+/// Read a key stored in the given environment file
+fn read_key(key_file: &Path, name: &str) -> anyhow::Result<Vec<u8>> {
+	let values = env_file::read_values(key_file)
+		.with_context(|| format!("Something went wrong while reading {}", key_file.display()))?;
+	let value = values.get(name)
+	                  .filter(|value| !env_file::is_placeholder(Some(value)))
+	                  .ok_or_else(|| CompanionError::Config(format!("{} is not defined in {}, run `make keys` first", name, key_file.display())))?;
+
+	base64_url_decode(value).with_context(|| format!("Something went wrong while decoding {}", name))
+}
+
+/// Read the value to seal from stdin
+fn read_stdin_value() -> anyhow::Result<String> {
+	let mut value = String::new();
+	std::io::stdin().read_to_string(&mut value)
+	                .with_context(|| "Cannot read the value to seal from stdin")?;
+
+	let value = value.trim_end_matches(['\r', '\n']);
+	if value.is_empty() {
+		anyhow::bail!(CompanionError::UserAbort("No value given on stdin, nothing sealed".to_owned()));
+	}
+
+	Ok(value.to_owned())
+}
+
+/// Seal a single value to the project asymmetric encryption public key
+pub fn seal(global_arguments: &global_args::GlobalArgs, arguments: &SealArgs) -> anyhow::Result<()> {
+	let key_file = arguments.key_file.as_deref().unwrap_or(&arguments.env);
+	let public_key = seal::PublicKey::try_from(
+		read_key(key_file, constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY)?.as_slice()
+	).map_err(|_| CompanionError::Crypto("Invalid asymmetric encryption public key length".to_owned()))?;
+
+	let value = match &arguments.value {
+		Some(value) => value.clone(),
+		None => read_stdin_value()?,
+	};
+
+	let mut ciphertext = vec![0u8; value.len() + seal::OVERHEAD_LENGTH];
+	seal::encrypt(value.as_bytes(), &public_key, &mut ciphertext)
+		.with_context(|| format!("Something went wrong while sealing {}", arguments.name))?;
+	let sealed_value = format!(
+		"{}{}",
+		SEALED_PREFIX,
+		base64_url(&ciphertext).with_context(|| "Something went wrong while encoding the sealed value")?,
+	);
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::update_values(&arguments.env, &[(arguments.name.clone(), sealed_value)])
+		.with_context(|| format!("Something went wrong while updating {}", arguments.env.display()))?;
+	info!("{} sealed into {}", arguments.name, arguments.env.display());
+
+	Ok(())
+}
+
+/// Unseal the sealed values using the project asymmetric encryption private key
+pub fn unseal(global_arguments: &global_args::GlobalArgs, arguments: &UnsealArgs) -> anyhow::Result<()> {
+	let key_file = arguments.key_file.as_deref().unwrap_or(&arguments.env);
+	let private_key = seal::PrivateKey::try_from(
+		read_key(key_file, constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY)?.as_slice()
+	).map_err(|_| CompanionError::Crypto("Invalid asymmetric encryption private key length".to_owned()))?;
+	let keypair = seal::Keypair::from_private_key(&private_key)
+		.with_context(|| "Something went wrong while loading the asymmetric encryption keypair")?;
+
+	let values = env_file::read_values(&arguments.env)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.env.display()))?;
+	let sealed_values: HashMap<&String, &str> = values.iter()
+	                                                  .filter(|(name, _)| arguments.names.is_empty() || arguments.names.contains(name))
+	                                                  .filter_map(|(name, value)| value.strip_prefix(SEALED_PREFIX).map(|value| (name, value)))
+	                                                  .collect();
+
+	if let Some(name) = arguments.names.iter().find(|name| !sealed_values.contains_key(name)) {
+		anyhow::bail!(CompanionError::Validation(format!("{} is not a sealed value", name)));
+	}
+
+	let mut unsealed_values = Vec::new();
+	for (name, sealed_value) in sealed_values {
+		debug!("Unsealing {}", name);
+
+		let ciphertext = base64_url_decode(sealed_value)
+			.with_context(|| format!("Something went wrong while decoding {}", name))?;
+		let mut plaintext = vec![0u8; ciphertext.len().saturating_sub(seal::OVERHEAD_LENGTH)];
+		seal::decrypt(&ciphertext, &keypair, &mut plaintext)
+			.with_context(|| format!("Cannot unseal {}, was it sealed with a different public key?", name))?;
+
+		unsealed_values.push((
+			name.clone(),
+			String::from_utf8(plaintext).with_context(|| format!("Unsealed {} is not valid UTF-8", name))?,
+		));
+	}
+	unsealed_values.sort();
+
+	if arguments.print {
+		for (name, value) in &unsealed_values {
+			print!("{}", env_file::format_line(name, value));
+		}
+		return Ok(());
+	}
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::update_values(&arguments.env, &unsealed_values)
+		.with_context(|| format!("Something went wrong while updating {}", arguments.env.display()))?;
+	info!("{} values unsealed into {}", unsealed_values.len(), arguments.env.display());
+
+	Ok(())
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+use log::{info, warn};
+
+use crate::env::{env_file, table};
+use crate::events;
+use crate::global_args;
+use crate::make::keys::{constants, generate_values, recover_public_key, select_variables};
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+	/// Environment file to compare, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// Example environment file to compare against
+	#[arg(long, default_value = ".env.example")]
+	pub(super) example: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+	/// Environment file to complete, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// Example environment file to read the missing variables from
+	#[arg(long, default_value = ".env.example")]
+	pub(super) example: PathBuf,
+}
+
+/// Variables defined in only one of the two files
+struct Differences {
+	/// Variables defined in the example but not in the environment file, with their example value
+	missing: Vec<(String, String)>,
+	/// Variables defined in the environment file but not in the example
+	extra: Vec<String>,
+}
+
+/// Compare the environment file against the example
+fn compare(env: &Path, example: &Path) -> anyhow::Result<(HashMap<String, String>, Differences)> {
+	// `-` reads the example from stdin
+	env_file::ensure_exists(example)
+		.with_context(|| format!("Example environment file {} cannot be read", example.display()))?;
+
+	let env_entries = env_file::read_entries(env)
+		.with_context(|| format!("Something went wrong while reading {}", env.display()))?;
+	let example_entries = env_file::read_entries(example)
+		.with_context(|| format!("Something went wrong while reading {}", example.display()))?;
+
+	let missing = example_entries.iter()
+	                             .filter(|(name, _)| !env_entries.iter().any(|(variable, _)| variable == name))
+	                             .cloned()
+	                             .collect();
+	let extra = env_entries.iter()
+	                       .filter(|(name, _)| !example_entries.iter().any(|(variable, _)| variable == name))
+	                       .map(|(name, _)| name.clone())
+	                       .collect();
+
+	Ok((env_entries.into_iter().collect(), Differences { missing, extra }))
+}
+
+/// Report the variables defined in only one of the environment file and its example
+pub fn diff(global_arguments: &global_args::GlobalArgs, arguments: &DiffArgs) -> anyhow::Result<()> {
+	let (_, differences) = compare(&arguments.env, &arguments.example)?;
+
+	let rows: Vec<(String, String)> = differences.missing
+	                                             .iter()
+	                                             .map(|(name, _)| (name.clone(), format!("missing from {}", arguments.env.display())))
+	                                             .chain(
+		                                             differences.extra
+		                                                        .iter()
+		                                                        .map(|name| (name.clone(), format!("not in {}", arguments.example.display())))
+	                                             )
+	                                             .collect();
+
+	if rows.is_empty() {
+		info!("{} and {} define the same variables", arguments.env.display(), arguments.example.display());
+		return Ok(());
+	}
+
+	if !global_arguments.json {
+		table::display_differences_table(&rows);
+	} else {
+		events::insert_json("missing", &differences.missing.iter().map(|(name, _)| name).collect::<Vec<_>>())?;
+		events::insert_json("extra", &differences.extra)?;
+	}
+
+	warn!(
+		"{} variables missing from {}, {} variables not in {}",
+		differences.missing.len(),
+		arguments.env.display(),
+		differences.extra.len(),
+		arguments.example.display(),
+	);
+
+	Ok(())
+}
+
+/// Append the variables missing from the environment file, generating the managed keys
+pub fn sync(global_arguments: &global_args::GlobalArgs, arguments: &SyncArgs) -> anyhow::Result<()> {
+	let (existing_values, differences) = compare(&arguments.env, &arguments.example)?;
+
+	if differences.missing.is_empty() {
+		info!("No variable missing from {}", arguments.env.display());
+		return Ok(());
+	}
+
+	// managed keys are only generated when missing, a missing public key is recovered from the
+	// private key already in the file
+	let missing_managed_names: Vec<String> = differences.missing
+	                                                    .iter()
+	                                                    .map(|(name, _)| name.clone())
+	                                                    .filter(|name| constants::MANAGED_ENV_VARIABLES.contains(&name.as_str()))
+	                                                    .collect();
+	let mut generated_values = HashMap::new();
+	if !missing_managed_names.is_empty() {
+		let selected = select_variables(&missing_managed_names, true, &existing_values)?;
+		generated_values = generate_values(&selected)?;
+		recover_public_key(&selected, &existing_values, &mut generated_values)?;
+	}
+
+	// a keypair generated for a placeholder already in the file cannot be added half way, existing
+	// variables are never touched
+	if generated_values.keys().any(|name| existing_values.contains_key(*name)) {
+		warn!(
+			"The asymmetric keypair is incomplete in {}, run `make keys --skip-existing` to generate it",
+			arguments.env.display(),
+		);
+		generated_values.remove(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY);
+		generated_values.remove(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY);
+	}
+
+	let mut values: Vec<(String, String)> = Vec::new();
+	for (name, example_value) in &differences.missing {
+		match generated_values.get(name.as_str()) {
+			Some(value) => {
+				info!("Adding {} with a freshly generated value", name);
+				values.push((name.clone(), value.clone()));
+			}
+			None => {
+				info!("Adding {} with the example value", name);
+				values.push((name.clone(), example_value.clone()));
+			}
+		}
+	}
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::update_values(&arguments.env, &values)
+		.with_context(|| format!("Something went wrong while updating {}", arguments.env.display()))?;
+	info!("{} variables added to {}", differences.missing.len(), arguments.env.display());
+
+	Ok(())
+}
@@ -0,0 +1,52 @@
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Attribute, Cell, Row, Table};
+
+use crate::env::schema::EnvCheckReport;
+
+/// Display the environment variables table
+pub fn display_variables_table(variables: &[(String, String)]) {
+	let mut table = Table::new();
+	table.load_preset(UTF8_FULL)
+	     .set_header(vec![
+		     Cell::new("Environment variable name").add_attribute(Attribute::Bold),
+		     Cell::new("Value").add_attribute(Attribute::Bold),
+	     ])
+	     .add_rows(variables.iter().map(|(name, value)| Row::from(vec![name, value])));
+
+	println!("{table}");
+}
+
+/// Display the differences between two environment files
+pub fn display_differences_table(differences: &[(String, String)]) {
+	let mut table = Table::new();
+	table.load_preset(UTF8_FULL)
+	     .set_header(vec![
+		     Cell::new("Environment variable name").add_attribute(Attribute::Bold),
+		     Cell::new("Status").add_attribute(Attribute::Bold),
+	     ])
+	     .add_rows(differences.iter().map(|(name, status)| Row::from(vec![name, status])));
+
+	println!("{table}");
+}
+
+/// Display the result of the check of an environment file against its schema
+pub fn display_check_table(report: &EnvCheckReport) {
+	let mut table = Table::new();
+	table.load_preset(UTF8_FULL)
+	     .set_header(vec![
+		     Cell::new("Environment variable name").add_attribute(Attribute::Bold),
+		     Cell::new("Status").add_attribute(Attribute::Bold),
+		     Cell::new("Details").add_attribute(Attribute::Bold),
+	     ])
+	     .add_rows(
+		     report.results
+		           .iter()
+		           .map(|result| Row::from(vec![
+			           result.name().to_owned(),
+			           format!("{:?}", result.status()),
+			           result.details().to_owned(),
+		           ]))
+	     );
+
+	println!("{table}");
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use log::{info, warn};
+
+use crate::env::{env_file, table};
+use crate::error::CompanionError;
+use crate::events;
+use crate::global_args;
+use crate::helpers::mask;
+
+#[derive(Args, Debug)]
+pub struct SetArgs {
+	/// Variables to set, in the `KEY=VALUE` format
+	#[arg(required = true, value_parser = parse_assignment)]
+	assignments: Vec<(String, String)>,
+
+	/// File to update, starting from the current working directory
+	#[arg(long, short, visible_alias = "file", default_value = ".env")]
+	pub(super) env: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct GetArgs {
+	/// Name of the variable to read
+	name: String,
+
+	/// File to read, starting from the current working directory
+	#[arg(long, short, visible_alias = "file", default_value = ".env")]
+	pub(super) env: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct UnsetArgs {
+	/// Names of the variables to remove
+	#[arg(required = true)]
+	names: Vec<String>,
+
+	/// File to update, starting from the current working directory
+	#[arg(long, short, visible_alias = "file", default_value = ".env")]
+	pub(super) env: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+	/// File to read, starting from the current working directory
+	#[arg(long, short, visible_alias = "file", default_value = ".env")]
+	pub(super) env: PathBuf,
+
+	/// Show the values in clear instead of masking them
+	#[arg(long)]
+	reveal: bool,
+}
+
+/// Parse a `KEY=VALUE` assignment
+fn parse_assignment(assignment: &str) -> Result<(String, String), String> {
+	match assignment.split_once('=') {
+		Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_owned(), value.to_owned())),
+		_ => Err(format!("Invalid assignment '{}', expected KEY=VALUE", assignment)),
+	}
+}
+
+/// Set one or more variables, preserving the rest of the file
+pub fn set(global_arguments: &global_args::GlobalArgs, arguments: &SetArgs) -> anyhow::Result<()> {
+	let invalid: Vec<&str> = arguments.assignments
+	                                  .iter()
+	                                  .map(|(name, _)| name.as_str())
+	                                  .filter(|name| !env_file::is_valid_name(name))
+	                                  .collect();
+	if !invalid.is_empty() {
+		anyhow::bail!(CompanionError::Validation(format!(
+			"Invalid variable name(s) {}, expected letters, digits and underscores, not starting with a digit",
+			invalid.join(", "),
+		)));
+	}
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::update_values(&arguments.env, &arguments.assignments)
+		.with_context(|| format!("Something went wrong while updating {}", arguments.env.display()))?;
+
+	for (name, _) in &arguments.assignments {
+		info!("{} set in {}", name, arguments.env.display());
+	}
+
+	Ok(())
+}
+
+/// Print the raw value of a variable
+pub fn get(global_arguments: &global_args::GlobalArgs, arguments: &GetArgs) -> anyhow::Result<()> {
+	env_file::ensure_exists(&arguments.env)?;
+
+	let values = env_file::read_values(&arguments.env)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.env.display()))?;
+
+	let value = values.get(&arguments.name)
+	                  .ok_or_else(|| CompanionError::Validation(format!("{} is not defined in {}", arguments.name, arguments.env.display())))?;
+	if global_arguments.json {
+		log_mdc::insert("value", value);
+	} else {
+		println!("{}", value);
+	}
+
+	Ok(())
+}
+
+/// Remove one or more variables, preserving the rest of the file
+pub fn unset(global_arguments: &global_args::GlobalArgs, arguments: &UnsetArgs) -> anyhow::Result<()> {
+	env_file::ensure_exists(&arguments.env)?;
+
+	let values = env_file::read_values(&arguments.env)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.env.display()))?;
+
+	for name in arguments.names.iter().filter(|name| !values.contains_key(*name)) {
+		warn!("{} is not defined in {}", name, arguments.env.display());
+	}
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::remove_values(&arguments.env, &arguments.names)
+		.with_context(|| format!("Something went wrong while updating {}", arguments.env.display()))?;
+	info!("{} variables removed from {}", arguments.names.len(), arguments.env.display());
+
+	Ok(())
+}
+
+/// List all the variables, masking their values unless requested otherwise
+pub fn list(global_arguments: &global_args::GlobalArgs, arguments: &ListArgs) -> anyhow::Result<()> {
+	env_file::ensure_exists(&arguments.env)?;
+
+	let variables: BTreeMap<String, String> = env_file::read_values(&arguments.env)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.env.display()))?
+		.into_iter()
+		.map(|(name, value)| if arguments.reveal { (name, value) } else { (name, mask(&value)) })
+		.collect();
+
+	if !global_arguments.json {
+		table::display_variables_table(&variables.into_iter().collect::<Vec<_>>());
+	} else {
+		events::insert_json("variables", &variables)?;
+		info!("{} variables found in {}", variables.len(), arguments.env.display());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,188 @@
+//! Typed errors of the companion, each kind of failure exits with its own code.
+//!
+//! | kind         | exit code                                   |
+//! |--------------|---------------------------------------------|
+//! | `validation` | 1, as linters do for a failed check         |
+//! | `parse`      | 65 (`DATAERR`), e.g. a key which is not base64 |
+//! | `io`         | 66 (`NOINPUT`) when a file is missing, otherwise 74 (`IOERR`) |
+//! | `crypto`     | 77 (`NOPERM`)                               |
+//! | `config`     | 78 (`CONFIG`), e.g. a missing environment variable |
+//! | `user-abort` | 130, as an interrupted command              |
+//! | `plugin`     | the exit code of the external subcommand    |
+//! | `internal`   | 70 (`SOFTWARE`), any other failure          |
+//!
+//! Commands keep returning `anyhow::Result`, the kind of a failure is found by looking for a
+//! [`CompanionError`], or an error of a known library, in its context chain.
+
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use alkali::AlkaliError;
+use exitcode::ExitCode;
+use serde::Serialize;
+
+/// Exit code of a run failing validation, e.g. `env check` or `make keys --verify`
+pub const VALIDATION_FAILED: ExitCode = 1;
+
+/// Exit code of a run aborted by the user
+pub const USER_ABORTED: ExitCode = 130;
+
+/// Kind of failure of a run
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+	Io,
+	Config,
+	Parse,
+	Crypto,
+	Validation,
+	UserAbort,
+	Plugin,
+	Internal,
+}
+
+/// Errors the companion commands fail with
+#[derive(Debug)]
+pub enum CompanionError {
+	/// A file cannot be found, read or written
+	Io(io::Error),
+	/// The arguments or the configuration are invalid
+	Config(String),
+	/// Some content cannot be parsed
+	Parse(String),
+	/// A cryptographic operation failed, e.g. a wrong passphrase
+	Crypto(String),
+	/// The values do not pass validation
+	Validation(String),
+	/// The user aborted the operation, e.g. by closing stdin instead of typing a value
+	UserAbort(String),
+	/// An external subcommand failed, with its exit code
+	Plugin(String, ExitCode),
+}
+
+impl CompanionError {
+	/// Get the kind of the error
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			CompanionError::Io(_) => ErrorKind::Io,
+			CompanionError::Config(_) => ErrorKind::Config,
+			CompanionError::Parse(_) => ErrorKind::Parse,
+			CompanionError::Crypto(_) => ErrorKind::Crypto,
+			CompanionError::Validation(_) => ErrorKind::Validation,
+			CompanionError::UserAbort(_) => ErrorKind::UserAbort,
+			CompanionError::Plugin(_, _) => ErrorKind::Plugin,
+		}
+	}
+
+	/// Get the code the process exits with
+	pub fn exit_code(&self) -> ExitCode {
+		match self {
+			CompanionError::Io(error) => io_exit_code(error),
+			CompanionError::Config(_) => exitcode::CONFIG,
+			CompanionError::Parse(_) => exitcode::DATAERR,
+			CompanionError::Crypto(_) => exitcode::NOPERM,
+			CompanionError::Validation(_) => VALIDATION_FAILED,
+			CompanionError::UserAbort(_) => USER_ABORTED,
+			CompanionError::Plugin(_, exit_code) => *exit_code,
+		}
+	}
+}
+
+impl Display for CompanionError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			CompanionError::Io(error) => error.fmt(f),
+			CompanionError::Config(message) |
+			CompanionError::Parse(message) |
+			CompanionError::Crypto(message) |
+			CompanionError::Validation(message) |
+			CompanionError::UserAbort(message) |
+			CompanionError::Plugin(message, _) => f.write_str(message),
+		}
+	}
+}
+
+impl std::error::Error for CompanionError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			CompanionError::Io(error) => Some(error),
+			_ => None,
+		}
+	}
+}
+
+impl From<io::Error> for CompanionError {
+	fn from(error: io::Error) -> Self {
+		CompanionError::Io(error)
+	}
+}
+
+/// Exit code of an io error, missing files are told apart from the other failures
+fn io_exit_code(error: &io::Error) -> ExitCode {
+	match error.kind() {
+		io::ErrorKind::NotFound => exitcode::NOINPUT,
+		_ => exitcode::IOERR,
+	}
+}
+
+/// Find the kind and exit code of a failure from the first typed error of its context chain
+pub fn classify(error: &anyhow::Error) -> (ErrorKind, ExitCode) {
+	// errors attached with `.context(...)` are only reachable from the outermost error
+	if let Some(error) = error.downcast_ref::<CompanionError>() {
+		return (error.kind(), error.exit_code());
+	}
+
+	error.chain()
+	     .find_map(|cause| {
+		     if let Some(error) = cause.downcast_ref::<CompanionError>() {
+			     Some((error.kind(), error.exit_code()))
+		     } else if let Some(error) = cause.downcast_ref::<io::Error>() {
+			     Some((ErrorKind::Io, io_exit_code(error)))
+		     } else if cause.is::<std::env::VarError>() {
+			     // secrets and passphrases are passed through environment variables
+			     Some((ErrorKind::Config, exitcode::CONFIG))
+		     } else if let Some(error) = cause.downcast_ref::<AlkaliError>() {
+			     // a key which is not base64 or has the wrong length is malformed data, not a crypto failure
+			     match error {
+				     AlkaliError::DecodeError | AlkaliError::IncorrectSliceLength => Some((ErrorKind::Parse, exitcode::DATAERR)),
+				     _ => Some((ErrorKind::Crypto, exitcode::NOPERM)),
+			     }
+		     } else if cause.is::<serde_json::Error>() || cause.is::<toml::de::Error>() || cause.is::<serde_yaml::Error>() {
+			     Some((ErrorKind::Parse, exitcode::DATAERR))
+		     } else {
+			     None
+		     }
+	     })
+	     .unwrap_or((ErrorKind::Internal, exitcode::SOFTWARE))
+}
+
+#[test]
+fn can_classify_errors() {
+	use anyhow::Context;
+
+	let error = anyhow::Error::from(CompanionError::Validation("Environment file check failed".to_owned()));
+	assert_eq!(classify(&error), (ErrorKind::Validation, VALIDATION_FAILED));
+
+	let error = std::fs::read_to_string("/nonexistent/.env").context("Something went wrong while reading .env").unwrap_err();
+	assert_eq!(classify(&error), (ErrorKind::Io, exitcode::NOINPUT));
+
+	let error = toml::from_str::<toml::Value>("=").context("Invalid TOML schema").unwrap_err();
+	assert_eq!(classify(&error), (ErrorKind::Parse, exitcode::DATAERR));
+
+	let error = std::env::var("SAAS_COMPANION_UNDEFINED_VARIABLE").context("Cannot read the passphrase").unwrap_err();
+	assert_eq!(classify(&error), (ErrorKind::Config, exitcode::CONFIG));
+
+	// the typed context wins over the underlying error
+	let error = Err::<(), _>(io::Error::from(io::ErrorKind::Other))
+		.context(CompanionError::Config("Invalid configuration".to_owned()))
+		.unwrap_err();
+	assert_eq!(classify(&error), (ErrorKind::Config, exitcode::CONFIG));
+
+	let error = crate::helpers::base64_url_decode("not base64!").context("Something went wrong while decoding NEXTAUTH_SECRET").unwrap_err();
+	assert_eq!(classify(&error), (ErrorKind::Parse, exitcode::DATAERR));
+
+	let error = anyhow::Error::from(CompanionError::UserAbort("No value to seal".to_owned()));
+	assert_eq!(classify(&error), (ErrorKind::UserAbort, USER_ABORTED));
+
+	assert_eq!(classify(&anyhow::anyhow!("Something went wrong")), (ErrorKind::Internal, exitcode::SOFTWARE));
+}
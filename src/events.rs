@@ -0,0 +1,133 @@
+//! Structured JSON output of the `--json` mode.
+//!
+//! Every line printed is an [`Event`] object with a stable, versioned schema:
+//!
+//! ```json
+//! {"version":1,"event":"log","command":"make keys","level":"info","data":{"message":"Generating symmetric encryption keys"},"ts":"2023-11-12T05:29:04.294446+01:00"}
+//! {"version":1,"event":"result","command":"make keys","level":"info","data":{"variables":{...}},"ts":"2023-11-12T05:29:04.295012+01:00"}
+//! ```
+//!
+//! - `log` events are emitted for each log message, `data.message` holds the message
+//! - a single `result` event ends every run, `data` holds the output the command attached, plain
+//!   values inserted with `log_mdc::insert` are always strings while structured payloads inserted
+//!   with [`insert_json`] are embedded as JSON, or `data.error` when the command failed, holding the error
+//!   `message`, its `causes`, its `kind` and the `exit_code` of the run, see the
+//!   [error](crate::error) module
+
+use anyhow::Context;
+use log::Record;
+use log4rs::encode::{self, Encode};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::error;
+use crate::structures::event::{Event, EventKind};
+
+/// Encoder writing the log records as `log` events
+#[derive(Debug)]
+pub struct EventEncoder {
+	command: String,
+}
+
+impl EventEncoder {
+	/// Create an encoder tagging the events with the given command
+	pub fn new(command: impl Into<String>) -> Self {
+		Self { command: command.into() }
+	}
+}
+
+impl Encode for EventEncoder {
+	fn encode(&self, w: &mut dyn encode::Write, record: &Record) -> anyhow::Result<()> {
+		let event = Event::new(EventKind::Log, &self.command, record.level(), json!({ "message": record.args().to_string() }));
+		let line: String = event.into();
+
+		w.write_all(line.as_bytes())?;
+		w.write_all(b"\n")?;
+
+		Ok(())
+	}
+}
+
+/// Prefix of the mapped diagnostic context keys holding a JSON payload
+const JSON_KEY_PREFIX: &str = "json:";
+
+/// Attach a structured payload to the `result` event, embedded as JSON under the given key
+pub fn insert_json<T: Serialize + ?Sized>(key: &str, value: &T) -> anyhow::Result<()> {
+	let value = serde_json::to_string(value).with_context(|| format!("Cannot serialize the {} result", key))?;
+	log_mdc::insert(format!("{}{}", JSON_KEY_PREFIX, key), value);
+
+	Ok(())
+}
+
+/// Collect the output the command attached to the mapped diagnostic context, only the payloads
+/// inserted with [`insert_json`] are embedded as JSON
+fn command_output() -> Map<String, Value> {
+	let mut output = Map::new();
+
+	log_mdc::iter(|key, value| {
+		match key.strip_prefix(JSON_KEY_PREFIX) {
+			Some(key) => {
+				let value = serde_json::from_str::<Value>(value).unwrap_or_else(|_| Value::String(value.to_owned()));
+				output.insert(key.to_owned(), value);
+			}
+			None => {
+				output.insert(key.to_owned(), Value::String(value.to_owned()));
+			}
+		}
+	});
+
+	output
+}
+
+/// Build the `result` event ending a run
+pub fn result_event(command: &str, outcome: &anyhow::Result<()>) -> Event {
+	let mut data = command_output();
+
+	let level = match outcome {
+		Ok(()) => log::Level::Info,
+		Err(error) => {
+			let (kind, exit_code) = error::classify(error);
+			data.insert(
+				"error".to_owned(),
+				json!({
+					"message": error.to_string(),
+					"causes": error.chain().skip(1).map(|cause| cause.to_string()).collect::<Vec<_>>(),
+					"kind": kind,
+					"exit_code": exit_code,
+				}),
+			);
+			log::Level::Error
+		}
+	};
+
+	Event::new(EventKind::Result, command, level, Value::Object(data))
+}
+
+#[test]
+fn can_build_result_events() {
+	insert_json("report", &json!({ "valid": true })).unwrap();
+	log_mdc::insert("name", "value");
+	log_mdc::insert("object", r#"{"a":1}"#);
+
+	let event: String = result_event("env check", &Ok(())).into();
+	let event: Value = serde_json::from_str(&event).unwrap();
+	assert_eq!(event["version"], 1);
+	assert_eq!(event["event"], "result");
+	assert_eq!(event["command"], "env check");
+	assert_eq!(event["level"], "info");
+	assert_eq!(event["data"]["report"]["valid"], true);
+	assert_eq!(event["data"]["name"], "value");
+	// plain values stay strings whatever they hold
+	assert_eq!(event["data"]["object"], r#"{"a":1}"#);
+
+	let error = anyhow::anyhow!("root cause").context("Something went wrong");
+	let event: String = result_event("env check", &Err(error)).into();
+	let event: Value = serde_json::from_str(&event).unwrap();
+	assert_eq!(event["level"], "error");
+	assert_eq!(event["data"]["error"]["message"], "Something went wrong");
+	assert_eq!(event["data"]["error"]["causes"][0], "root cause");
+	assert_eq!(event["data"]["error"]["kind"], "internal");
+	assert_eq!(event["data"]["error"]["exit_code"], 70);
+
+	log_mdc::clear();
+}
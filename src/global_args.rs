@@ -1,3 +1,9 @@
+/// Size in bytes after which the log file is rotated, unless configured otherwise
+pub const DEFAULT_LOG_FILE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Number of rotated log files kept, unless configured otherwise
+pub const DEFAULT_LOG_FILE_COUNT: u32 = 5;
+
 #[derive(clap::Args, Debug)]
 pub struct GlobalArgs {
     /// Run a command without applying any modification
@@ -8,6 +14,18 @@ pub struct GlobalArgs {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Also write the log messages to the given file, rotated once it grows past the maximum size
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<std::path::PathBuf>,
+
+    /// Size in bytes after which the log file is rotated
+    #[arg(long, global = true, value_name = "BYTES", default_value_t = DEFAULT_LOG_FILE_MAX_SIZE)]
+    pub log_file_max_size: u64,
+
+    /// Number of rotated log files to keep, as `<PATH>.1` to `<PATH>.<COUNT>`
+    #[arg(long, global = true, value_name = "COUNT", default_value_t = DEFAULT_LOG_FILE_COUNT)]
+    pub log_file_count: u32,
+
     /// Set the message verbosity levels
     #[command(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity<clap_verbosity_flag::InfoLevel>,
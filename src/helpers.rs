@@ -1,6 +1,66 @@
-use alkali::{AlkaliError, encode::base64};
+use alkali::{AlkaliError, encode::base64, hash::generic};
+
+/// Create a standard (RFC 4648, section 4) base64 encoded version of the provided bytecodes
+pub fn base64_standard(bytes: &[u8]) -> Result<String, AlkaliError> {
+	base64::encode(bytes, base64::Variant::Original)
+}
 
 /// Create a base64 url encoded version of the provided bytecodes
 pub fn base64_url(bytes: &[u8]) -> Result<String, AlkaliError> {
 	base64::encode(bytes, base64::Variant::URLSafe)
-}
\ No newline at end of file
+}
+
+/// Create a base64 url encoded version of the provided bytecodes without padding, as required by
+/// JOSE (RFC 7515, section 2)
+pub fn base64_url_no_padding(bytes: &[u8]) -> Result<String, AlkaliError> {
+	base64::encode(bytes, base64::Variant::URLSafeNoPadding)
+}
+
+/// Decode a base64 url encoded string into its bytecodes
+pub fn base64_url_decode(value: &str) -> Result<Vec<u8>, AlkaliError> {
+	let mut buffer = vec![0u8; value.len() / 4 * 3 + 3];
+	let length = base64::decode(value, base64::Variant::URLSafe, &mut buffer)?;
+	buffer.truncate(length);
+
+	Ok(buffer)
+}
+
+/// Short fingerprint of a value as stored in the environment file, the first bytes of its BLAKE2b
+/// digest hex encoded, allowing to tell two values apart without disclosing them
+pub fn fingerprint(value: &str) -> Result<String, AlkaliError> {
+	const FINGERPRINT_BYTES: usize = 4;
+
+	let digest = generic::hash_custom_to_vec(value.as_bytes(), None, generic::DIGEST_LENGTH_MIN)?;
+
+	Ok(digest.iter().take(FINGERPRINT_BYTES).map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Mask a secret value, only keeping its first characters and its fingerprint visible
+pub fn mask(value: &str) -> String {
+	const VISIBLE_CHARACTERS: usize = 4;
+
+	if value.is_empty() {
+		return String::new();
+	}
+
+	let prefix = if value.chars().count() <= VISIBLE_CHARACTERS * 2 {
+		String::new()
+	} else {
+		value.chars().take(VISIBLE_CHARACTERS).collect::<String>()
+	};
+
+	match fingerprint(value) {
+		Ok(fingerprint) => format!("{}{}#{}", prefix, "*".repeat(8), fingerprint),
+		Err(_) => format!("{}{}", prefix, "*".repeat(8)),
+	}
+}
+
+#[test]
+fn can_mask_values() {
+	assert_eq!(mask(""), "");
+	assert!(mask("short").starts_with("********#"));
+	assert!(mask("sk_test_1234567890").starts_with("sk_t********#"));
+	assert!(!mask("sk_test_1234567890").contains("1234567890"));
+	assert_eq!(mask("sk_test_1234567890"), mask("sk_test_1234567890"));
+	assert_ne!(mask("sk_test_1234567890"), mask("sk_test_0987654321"));
+}
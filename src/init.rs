@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use clap::Args;
+use log::{info, trace, warn};
+
+use crate::config::{CompanionConfig, DEFAULT_CONFIG_FILE};
+use crate::env::env_file;
+use crate::error::CompanionError;
+use crate::events;
+use crate::global_args;
+use crate::make::keys::constants;
+use crate::make::{keys, signatures};
+
+/// Lock files of the package managers, with the command installing the dependencies
+const PACKAGE_MANAGERS: [(&str, &str); 4] = [
+	("pnpm-lock.yaml", "pnpm install"),
+	("yarn.lock", "yarn install"),
+	("bun.lockb", "bun install"),
+	("package-lock.json", "npm install"),
+];
+
+#[derive(Args, Debug)]
+pub struct InitArgs {
+	/// Environment file to create, starting from the current working directory
+	#[arg(long, short, default_value = ".env")]
+	env: PathBuf,
+
+	/// Example environment file the environment file is created from
+	#[arg(long, default_value = ".env.example")]
+	example: PathBuf,
+
+	/// Overwrite the configuration file if it already exists
+	#[arg(long)]
+	force: bool,
+}
+
+/// List the markers of the SaaS template missing from the project directory, the template is
+/// detected when the list is empty
+pub fn missing_template_markers(directory: &Path, example: &Path) -> anyhow::Result<Vec<String>> {
+	let mut missing = Vec::new();
+
+	if !directory.join("package.json").exists() {
+		missing.push("package.json".to_owned());
+	}
+
+	let example = directory.join(example);
+	if !example.exists() {
+		missing.push(example.display().to_string());
+	} else {
+		let entries = env_file::read_entries(&example)
+			.with_context(|| format!("Something went wrong while reading {}", example.display()))?;
+		missing.extend(
+			constants::MANAGED_ENV_VARIABLES.iter()
+			                                .filter(|name| !entries.iter().any(|(other, _)| other == *name))
+			                                .map(|name| format!("{} in {}", name, example.display())),
+		);
+	}
+
+	Ok(missing)
+}
+
+/// List the manual steps left once the project is initialized
+pub fn checklist(directory: &Path, config: &CompanionConfig, entries: &[(String, String)]) -> Vec<String> {
+	let mut steps: Vec<String> = entries.iter()
+	                                    .filter(|(name, _)| !constants::MANAGED_ENV_VARIABLES.contains(&name.as_str()))
+	                                    .filter(|(_, value)| env_file::is_placeholder(Some(value)))
+	                                    .map(|(name, _)| format!("Set {} in {}", name, config.env.display()))
+	                                    .collect();
+
+	let install = PACKAGE_MANAGERS.iter()
+	                              .find(|(lock_file, _)| directory.join(lock_file).exists())
+	                              .map_or("npm install", |(_, command)| command);
+	steps.push(format!("Install the dependencies with `{}`", install));
+
+	if !directory.join(&config.schema).exists() {
+		steps.push(format!("Declare the environment variables in {} to enable `env check`", config.schema.display()));
+	}
+	steps.push(format!("Review the settings in {}", DEFAULT_CONFIG_FILE));
+
+	steps
+}
+
+/// Scaffold the configuration and the environment of a freshly cloned SaaS template
+pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &InitArgs) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	trace!("{:?}", arguments);
+
+	let directory = std::env::current_dir().with_context(|| "Something went wrong while reading the working directory")?;
+	let missing = missing_template_markers(&directory, &arguments.example)?;
+	if !missing.is_empty() {
+		anyhow::bail!(CompanionError::Config(format!(
+			"No SaaS template found in {}, missing {}",
+			directory.display(),
+			missing.join(", "),
+		)));
+	}
+	info!("SaaS template detected in {}", directory.display());
+
+	let config = CompanionConfig {
+		env: arguments.env.clone(),
+		example: arguments.example.clone(),
+		..CompanionConfig::default()
+	};
+	let config_file = PathBuf::from(DEFAULT_CONFIG_FILE);
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+	} else {
+		if config_file.exists() && !arguments.force {
+			info!("{} already exists, keeping it", config_file.display());
+		} else {
+			env_file::write_public_content(&config_file, &config.render())
+				.with_context(|| format!("Something went wrong while writing {}", config_file.display()))?;
+			info!("Configuration written to {}", config_file.display());
+		}
+
+		if arguments.env.exists() {
+			info!("{} already exists, keeping it", arguments.env.display());
+		} else {
+			// copying the file would carry over the permissions of the example, readable by everyone
+			let content = std::fs::read_to_string(&arguments.example)
+				.with_context(|| format!("Something went wrong while reading {}", arguments.example.display()))?;
+			env_file::write_content(&arguments.env, &content)
+				.with_context(|| format!("Something went wrong while creating {}", arguments.env.display()))?;
+			info!("{} created from {}", arguments.env.display(), arguments.example.display());
+		}
+
+		keys::handle(global_arguments, &keys::KeysArgs::fill_in(&arguments.env)?)
+			.with_context(|| "Something went wrong while making the encryption keys")?;
+		signatures::handle(global_arguments, &signatures::SignaturesArgs::default())
+			.with_context(|| "Something went wrong while making the procedure signatures")?;
+	}
+
+	// the environment file does not exist yet on a dry run
+	let source = if arguments.env.exists() { &arguments.env } else { &arguments.example };
+	let entries = env_file::read_entries(source)
+		.with_context(|| format!("Something went wrong while reading {}", source.display()))?;
+	let steps = checklist(&directory, &config, &entries);
+
+	if !global_arguments.json {
+		println!("Remaining steps:");
+		steps.iter().for_each(|step| println!("  [ ] {}", step));
+	} else {
+		events::insert_json("checklist", &steps)?;
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_list_remaining_steps() {
+	let directory = std::env::temp_dir();
+	let entries = vec![
+		("DATABASE_URL".to_owned(), "".to_owned()),
+		("PORT".to_owned(), "3000".to_owned()),
+		(constants::ENV_VARIABLE__NEXTAUTH_SECRET.to_owned(), "".to_owned()),
+	];
+
+	let steps = checklist(&directory, &CompanionConfig { schema: PathBuf::from("missing.schema.toml"), ..CompanionConfig::default() }, &entries);
+	assert_eq!(steps[0], "Set DATABASE_URL in .env");
+	assert!(steps[1].starts_with("Install the dependencies with"));
+	assert_eq!(steps[2], "Declare the environment variables in missing.schema.toml to enable `env check`");
+	assert_eq!(steps[3], "Review the settings in companion.toml");
+}
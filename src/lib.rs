@@ -1,8 +1,18 @@
 pub mod version;
 pub mod authors;
 pub mod cleanup;
+pub mod completions;
+pub mod config;
+pub mod doctor;
+pub mod env;
+pub mod error;
+pub mod events;
 pub mod global_args;
+pub mod init;
 pub mod make;
+pub mod man;
+pub mod plugins;
 pub mod helpers;
 pub mod macros;
+pub mod redaction;
 pub mod structures;
\ No newline at end of file
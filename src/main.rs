@@ -1,20 +1,33 @@
-use clap::{Parser, Subcommand};
-use log4rs::append::console::ConsoleAppender;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use log::{warn, LevelFilter};
+use log4rs::append::console::{ConsoleAppender, Target};
+use log4rs::append::rolling_file::RollingFileAppender;
+use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
 use log4rs::Config;
 use log4rs::config::{Appender, Root};
 use log4rs::encode::Encode;
-use log4rs::encode::json::JsonEncoder;
 use log4rs::encode::pattern::PatternEncoder;
-use saas_template_companion::{authors, cleanup, global_args, make, version};
+use log4rs::filter::{Filter, Response};
+use log4rs::filter::threshold::ThresholdFilter;
+use saas_template_companion::{authors, cleanup, completions, config, doctor, env, error, events, global_args, init, make, man, plugins, redaction, version};
+use saas_template_companion::redaction::RedactingEncoder;
 
 /// SaaS Template Companion, helps you in the management and run of common operations to ease the
 /// setup and creation of your SaaS
 #[derive(Parser, Debug)]
+#[allow(clippy::upper_case_acronyms)]
 #[command(name = "saas-template-companion", author, about, long_about = None, disable_help_subcommand = true, arg_required_else_help = true)]
 struct CLI {
-	/// Configuration file to load for a given command
-	#[arg(short, long, global = true)]
-	config: Option<std::path::PathBuf>,
+	/// Configuration file providing the default values of the options, `companion.toml` is loaded
+	/// when it exists
+	#[arg(short, long, global = true, value_name = "FILE")]
+	config: Option<PathBuf>,
 
 	#[command(flatten)]
 	global_args: global_args::GlobalArgs,
@@ -29,10 +42,30 @@ enum Command {
 	#[command()]
 	Cleanup(cleanup::CleanupArgs),
 
+	/// Diagnose the local project setup
+	#[command()]
+	Doctor(doctor::DoctorArgs),
+
+	/// Manage environment files
+	#[command()]
+	Env(env::EnvArgs),
+
+	/// Set up a freshly cloned SaaS template: configuration, environment file and keys
+	#[command()]
+	Init(init::InitArgs),
+
 	/// Make or generate something
 	#[command(visible_alias = "generate")]
 	Make(make::MakeArgs),
 
+	/// Print the shell completion script
+	#[command()]
+	Completions(completions::CompletionsArgs),
+
+	/// Generate the man pages of every command
+	#[command()]
+	Man(man::ManArgs),
+
 	/// Print version and exit
 	#[command()]
 	Version,
@@ -40,56 +73,205 @@ enum Command {
 	/// Print authors and exit
 	#[command()]
 	Authors,
+
+	/// Run the `saas-template-companion-<COMMAND>` executable found on PATH
+	#[command(external_subcommand)]
+	External(Vec<OsString>),
 }
 
-/// Set up the cli wide logger, messages can be logged using the `log` crate.
-///
-/// Log formats samples:
-///  - json: [reference](https://docs.rs/log4rs/1.2.0/log4rs/encode/json/index.html#contents)
-///  - text: `[2023-11-12 05:29:04.294446 +01:00] [TRACE] <message>`
-fn setup_logger(cli: &CLI) -> anyhow::Result<()> {
+/// Name of the command being run, made of the subcommand names, e.g. "make keys"
+fn command_path(matches: &ArgMatches) -> String {
+	let mut names = Vec::new();
+	let mut current = matches;
+
+	while let Some((name, subcommand_matches)) = current.subcommand() {
+		names.push(name);
+		current = subcommand_matches;
+	}
+
+	names.join(" ")
+}
+
+/// Whether the logs must go to stderr, as the command pipes its output through stdout
+fn logs_to_stderr(cli: &CLI) -> bool {
+	match &cli.command {
+		Command::Env(options) => options.writes_data_to_stdout(),
+		Command::Make(options) => options.writes_data_to_stdout(),
+		Command::Completions(_) => true,
+		_ => false,
+	}
+}
+
+/// Accept the log records less severe than warnings, the others are routed to stderr
+#[derive(Debug)]
+struct BelowWarnFilter;
+
+impl Filter for BelowWarnFilter {
+	fn filter(&self, record: &log::Record) -> Response {
+		if record.level() > log::Level::Warn { Response::Neutral } else { Response::Reject }
+	}
+}
+
+/// Build the encoder for the selected log format, registered secrets never reach the output
+fn encoder(cli: &CLI, command: &str) -> Box<dyn Encode> {
 	let encoder: Box<dyn Encode> = if cli.global_args.json {
-		Box::new(JsonEncoder::new())
+		Box::new(events::EventEncoder::new(command))
 	} else {
 		Box::new(PatternEncoder::new("[{d(%Y-%m-%d %H:%M:%S%.6f %Z)}] [{h({l})}] {m}{n}"))
 	};
 
+	Box::new(RedactingEncoder::new(encoder))
+}
+
+/// Set up the cli wide logger, messages can be logged using the `log` crate.
+/// Secret values registered with `redaction::register_secret` are masked in every log line.
+///
+/// Warnings and errors are written to stderr, so is everything else when the command pipes its
+/// output through stdout. With `--log-file`, every message is also written to a file rotated once
+/// it reaches `--log-file-max-size`.
+///
+/// Log formats samples:
+///  - json: `log` events, see the [events](saas_template_companion::events) module
+///  - text: `[2023-11-12 05:29:04.294446 +01:00] [TRACE] <message>`
+fn setup_logger(cli: &CLI, command: &str) -> anyhow::Result<()> {
+	// commands piping their output through stdout log to stderr only
+	let target = if logs_to_stderr(cli) { Target::Stderr } else { Target::Stdout };
+
 	let stdout: ConsoleAppender = ConsoleAppender::builder()
-		.encoder(encoder)
+		.encoder(encoder(cli, command))
+		.target(target)
+		.build();
+	let stderr: ConsoleAppender = ConsoleAppender::builder()
+		.encoder(encoder(cli, command))
+		.target(Target::Stderr)
 		.build();
 
-	let log_config = Config::builder()
+	let mut log_config = Config::builder()
 		.appender(
 			Appender::builder()
+				.filter(Box::new(BelowWarnFilter))
 				.build("stdout", Box::new(stdout))
 		)
-		.build(
-			Root::builder()
-				.appender("stdout")
-				.build(cli.global_args.verbose.log_level_filter())
-		)?;
+		.appender(
+			Appender::builder()
+				.filter(Box::new(ThresholdFilter::new(LevelFilter::Warn)))
+				.build("stderr", Box::new(stderr))
+		);
+	let mut root = Root::builder()
+		.appender("stdout")
+		.appender("stderr");
+
+	if let Some(path) = &cli.global_args.log_file {
+		let archive_pattern = format!("{}.{{}}", path.display());
+		let roller = FixedWindowRoller::builder()
+			.build(&archive_pattern, cli.global_args.log_file_count)
+			.with_context(|| format!("Something went wrong while setting up the rotation of {}", path.display()))?;
+		let policy = CompoundPolicy::new(
+			Box::new(SizeTrigger::new(cli.global_args.log_file_max_size)),
+			Box::new(roller),
+		);
+		let file = RollingFileAppender::builder()
+			.encoder(encoder(cli, command))
+			.build(path, Box::new(policy))
+			.with_context(|| format!("Something went wrong while opening the log file {}", path.display()))?;
+
+		log_config = log_config.appender(Appender::builder().build("file", Box::new(file)));
+		root = root.appender("file");
+	}
+
+	let log_config = log_config.build(root.build(cli.global_args.verbose.log_level_filter()))?;
 	log4rs::init_config(log_config)?;
 
 	Ok(())
 }
 
-fn main() -> anyhow::Result<()> {
-	let cli = CLI::parse();
+/// Load the configuration file given with `--config`, before the command line is fully parsed as
+/// the configuration provides the default values of the options.
+///
+/// Only a configuration file given with `--config` must be valid, the defaults are used instead of
+/// a broken `companion.toml`, along with the error to warn about.
+fn load_config() -> anyhow::Result<(config::CompanionConfig, Option<anyhow::Error>)> {
+	let matches = CLI::command().ignore_errors(true).try_get_matches().ok();
+	let path = matches.as_ref().and_then(|matches| matches.get_one::<PathBuf>("config"));
+	let subcommand = matches.as_ref().and_then(ArgMatches::subcommand_name);
+
+	match config::CompanionConfig::resolve(path.map(PathBuf::as_path)) {
+		Ok(config) => Ok((config, None)),
+		// doctor reports the broken configurations and init rewrites them
+		Err(error) if path.is_none() || matches!(subcommand, Some("doctor" | "init")) => {
+			Ok((config::CompanionConfig::default(), Some(error)))
+		}
+		Err(error) => Err(error),
+	}
+}
+
+fn main() {
+	let (config, config_error) = load_config().unwrap_or_else(|error| {
+		eprintln!("Error: {:?}", error);
+		std::process::exit(error::classify(&error).1);
+	});
 
-	setup_logger(&cli)?;
+	let matches = config.apply_defaults(CLI::command()).get_matches();
+	let cli = CLI::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+	let command = command_path(&matches);
 
-	match cli.command {
+	if let Err(error) = setup_logger(&cli, &command) {
+		eprintln!("Error: {:?}", error);
+		std::process::exit(error::classify(&error).1);
+	}
+
+	if let Some(error) = config_error {
+		warn!("{:#}, using the default settings", error);
+	}
+
+	let outcome = match &cli.command {
 		Command::Cleanup(options) => {
-			cleanup::handle(&cli.global_args, &options)
+			cleanup::handle(&cli.global_args, options)
+		}
+		Command::Doctor(options) => {
+			doctor::handle(&cli.global_args, options, cli.config.as_deref())
+		}
+		Command::Env(options) => {
+			env::handle(&cli.global_args, options)
+		}
+		Command::Init(options) => {
+			init::handle(&cli.global_args, options)
 		}
 		Command::Make(options) => {
-			make::handle(&cli.global_args, &options)
+			make::handle(&cli.global_args, options)
+		}
+		Command::Completions(options) => {
+			completions::handle(&cli.global_args, options, &mut CLI::command())
+		}
+		Command::Man(options) => {
+			man::handle(&cli.global_args, options, &mut CLI::command())
 		}
 		Command::Version => {
-			version::handle()
+			version::handle(&cli.global_args)
 		}
 		Command::Authors => {
-			authors::handle()
+			authors::handle(&cli.global_args)
+		}
+		Command::External(arguments) => {
+			plugins::handle(&cli.global_args, arguments)
+		}
+	};
+
+	// the result event ends every json run, even a failed one
+	if cli.global_args.json {
+		let event: String = events::result_event(&command, &outcome).into();
+		if logs_to_stderr(&cli) {
+			eprintln!("{}", redaction::redact(&event));
+		} else {
+			println!("{}", redaction::redact(&event));
+		}
+	}
+
+	// each kind of failure exits with its own code, see the error module
+	if let Err(error) = outcome {
+		if !cli.global_args.json {
+			eprintln!("Error: {}", redaction::redact(&format!("{:?}", error)));
 		}
+		std::process::exit(error::classify(&error).1);
 	}
 }
@@ -3,6 +3,7 @@ use log::trace;
 
 use crate::global_args;
 
+pub mod env_types;
 pub mod keys;
 pub mod signatures;
 
@@ -10,11 +11,15 @@ pub mod signatures;
 enum MakeSubCommand {
 	/// Generate and store new encryption keys
 	#[command()]
-	Keys(keys::KeysArgs),
+	Keys(Box<keys::KeysArgs>),
 
 	/// Remap the pre-generated procedure signatures
 	#[command()]
 	Signatures(signatures::SignaturesArgs),
+
+	/// Generate the TypeScript declarations and Zod validator of the environment variables
+	#[command()]
+	EnvTypes(env_types::EnvTypesArgs),
 }
 
 #[derive(Args, Debug)]
@@ -23,6 +28,16 @@ pub struct MakeArgs {
 	command: MakeSubCommand,
 }
 
+impl MakeArgs {
+	/// Whether stdout is reserved for the command output, logs must go elsewhere not to corrupt it
+	pub fn writes_data_to_stdout(&self) -> bool {
+		match &self.command {
+			MakeSubCommand::Keys(options) => options.writes_data_to_stdout(),
+			_ => false,
+		}
+	}
+}
+
 pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &MakeArgs) -> anyhow::Result<()> {
 	trace!("{:?}", global_arguments);
 	trace!("{:?}", arguments);
@@ -34,5 +49,8 @@ pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &MakeArgs)
 		MakeSubCommand::Signatures(options) => {
 			signatures::handle(global_arguments, options)
 		}
+		MakeSubCommand::EnvTypes(options) => {
+			env_types::handle(global_arguments, options)
+		}
 	}
 }
\ No newline at end of file
@@ -0,0 +1,236 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use log::{debug, info, trace, warn};
+
+use crate::env::env_file;
+use crate::env::schema::{EnvSchema, Required, VariableSchema, VariableType};
+use crate::error::CompanionError;
+use crate::global_args;
+use crate::make::keys::constants;
+
+/// Prefix of the variables exposed to the browser by Next.js
+const CLIENT_PREFIX: &str = "NEXT_PUBLIC_";
+
+/// Length in bytes of the keys generated by `make keys`
+const MANAGED_KEY_LENGTH: usize = 32;
+
+#[derive(Args, Debug)]
+pub struct EnvTypesArgs {
+	/// Example environment file listing the variables, starting from the current working directory
+	#[arg(long, default_value = ".env.example")]
+	example: PathBuf,
+
+	/// Schema file refining the variable types, used only when it exists
+	#[arg(long, default_value = "env.schema.toml")]
+	schema: PathBuf,
+
+	/// TypeScript declaration file to write
+	#[arg(long, default_value = "env.d.ts")]
+	declarations: PathBuf,
+
+	/// Zod validator file to write
+	#[arg(long, default_value = "env.ts")]
+	validator: PathBuf,
+}
+
+/// Header of the generated files
+fn header(source: &str) -> String {
+	format!("// Generated by {} from {}, do not edit manually\n", env!("CARGO_PKG_NAME"), source)
+}
+
+/// Collect the declared variables, in the example order followed by the managed keys it lacks
+fn collect_variables(example_names: Vec<String>, schema: &EnvSchema) -> Vec<(String, VariableSchema)> {
+	let mut names = example_names;
+	for name in constants::MANAGED_ENV_VARIABLES {
+		if !names.iter().any(|existing| existing == name) {
+			names.push(name.to_owned());
+		}
+	}
+
+	names.into_iter()
+	     .map(|name| {
+		     let declaration = schema.variables.get(&name).cloned().unwrap_or_else(|| VariableSchema {
+			     kind: if constants::MANAGED_ENV_VARIABLES.contains(&name.as_str()) {
+				     VariableType::Base64Key { length: MANAGED_KEY_LENGTH }
+			     } else {
+				     VariableType::String
+			     },
+			     required: Required::Always(true),
+			     description: None,
+		     });
+		     (name, declaration)
+	     })
+	     .collect()
+}
+
+/// Quote a string as a TypeScript literal
+fn quote(value: &str) -> String {
+	serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+/// TypeScript type of a variable in `ProcessEnv`
+fn typescript_type(kind: &VariableType) -> String {
+	match kind {
+		VariableType::Enum { values } => values.iter().map(|value| quote(value)).collect::<Vec<_>>().join(" | "),
+		_ => "string".to_owned(),
+	}
+}
+
+/// Zod validator of a variable, ignoring whether it is required
+fn zod_type(kind: &VariableType) -> String {
+	match kind {
+		VariableType::String => "z.string()".to_owned(),
+		VariableType::Url => "z.string().url()".to_owned(),
+		VariableType::Port => "z.coerce.number().int().min(1).max(65535)".to_owned(),
+		VariableType::Bool => r#"z.enum(["true", "false", "1", "0"]).transform((value) => value === "true" || value === "1")"#.to_owned(),
+		VariableType::Enum { values } => format!("z.enum([{}])", values.iter().map(|value| quote(value)).collect::<Vec<_>>().join(", ")),
+		// padded base64url length of the key
+		VariableType::Base64Key { length } => format!("z.string().regex(/^[A-Za-z0-9_-]+={{0,2}}$/).length({})", length.div_ceil(3) * 4),
+		VariableType::Email => "z.string().email()".to_owned(),
+	}
+}
+
+/// Zod validator of a variable, optional outside of the environments requiring it
+fn zod_validator(declaration: &VariableSchema) -> String {
+	let validator = zod_type(&declaration.kind);
+
+	match &declaration.required {
+		Required::Always(true) => validator,
+		Required::In(environments) if !environments.is_empty() => {
+			let condition = match environments.as_slice() {
+				[environment] => format!("process.env.NODE_ENV === {}", quote(environment)),
+				_ => format!(
+					"[{}].includes(process.env.NODE_ENV ?? \"\")",
+					environments.iter().map(|environment| quote(environment)).collect::<Vec<_>>().join(", "),
+				),
+			};
+			format!("{} ? {} : {}.optional()", condition, validator, validator)
+		}
+		_ => format!("{}.optional()", validator),
+	}
+}
+
+/// Documentation comment of a variable, indented for its block
+fn documentation(declaration: &VariableSchema, indentation: &str) -> String {
+	match &declaration.description {
+		Some(description) => format!("{}/** {} */\n", indentation, description),
+		None => String::new(),
+	}
+}
+
+/// Render the `ProcessEnv` declaration file
+pub fn render_declarations(source: &str, variables: &[(String, VariableSchema)]) -> String {
+	let mut content = header(source);
+	content.push_str("declare global {\n\tnamespace NodeJS {\n\t\tinterface ProcessEnv {\n");
+
+	for (name, declaration) in variables {
+		let optional = if matches!(declaration.required, Required::Always(true)) { "" } else { "?" };
+		content.push_str(&documentation(declaration, "\t\t\t"));
+		content.push_str(&format!("\t\t\t{}{}: {};\n", name, optional, typescript_type(&declaration.kind)));
+	}
+
+	content.push_str("\t\t}\n\t}\n}\n\nexport {};\n");
+	content
+}
+
+/// Render the t3-style `createEnv` validator file
+pub fn render_validator(source: &str, variables: &[(String, VariableSchema)]) -> String {
+	let render_block = |client: bool| {
+		variables.iter()
+		         .filter(|(name, _)| name.starts_with(CLIENT_PREFIX) == client)
+		         .map(|(name, declaration)| format!("{}\t\t{}: {},\n", documentation(declaration, "\t\t"), name, zod_validator(declaration)))
+		         .collect::<String>()
+	};
+
+	let mut content = header(source);
+	content.push_str("import { createEnv } from \"@t3-oss/env-nextjs\";\nimport { z } from \"zod\";\n\n");
+	content.push_str("export const env = createEnv({\n");
+	content.push_str(&format!("\tserver: {{\n{}\t}},\n", render_block(false)));
+	content.push_str(&format!("\tclient: {{\n{}\t}},\n", render_block(true)));
+	content.push_str("\truntimeEnv: {\n");
+	for (name, _) in variables {
+		content.push_str(&format!("\t\t{}: process.env.{},\n", name, name));
+	}
+	content.push_str("\t},\n});\n");
+	content
+}
+
+pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &EnvTypesArgs) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	trace!("{:?}", arguments);
+
+	if !arguments.example.exists() {
+		anyhow::bail!(CompanionError::Io(std::io::Error::new(
+			std::io::ErrorKind::NotFound,
+			format!("Example environment file {} does not exist", arguments.example.display()),
+		)));
+	}
+
+	let example_names = env_file::read_entries(&arguments.example)
+		.with_context(|| format!("Something went wrong while reading {}", arguments.example.display()))?
+		.into_iter()
+		.map(|(name, _)| name)
+		.collect();
+	let schema = if arguments.schema.exists() {
+		debug!("Reading the variable types from {}", arguments.schema.display());
+		EnvSchema::load(&arguments.schema)?
+	} else {
+		EnvSchema::default()
+	};
+
+	let variables = collect_variables(example_names, &schema);
+	let source = arguments.example.file_name().and_then(|name| name.to_str()).unwrap_or(".env.example");
+	let declarations = render_declarations(source, &variables);
+	let validator = render_validator(source, &variables);
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	env_file::write_public_content(&arguments.declarations, &declarations)
+		.with_context(|| format!("Something went wrong while writing {}", arguments.declarations.display()))?;
+	info!("TypeScript environment declarations written to {}", arguments.declarations.display());
+
+	env_file::write_public_content(&arguments.validator, &validator)
+		.with_context(|| format!("Something went wrong while writing {}", arguments.validator.display()))?;
+	info!("Zod environment validator written to {}", arguments.validator.display());
+
+	Ok(())
+}
+
+#[test]
+fn can_render_zod_validators() {
+	let declaration = |kind, required| VariableSchema { kind, required, description: None };
+
+	assert_eq!(zod_validator(&declaration(VariableType::Url, Required::Always(true))), "z.string().url()");
+	assert_eq!(zod_validator(&declaration(VariableType::Email, Required::Always(false))), "z.string().email().optional()");
+	assert_eq!(
+		zod_validator(&declaration(VariableType::String, Required::In(vec!["production".to_owned()]))),
+		"process.env.NODE_ENV === \"production\" ? z.string() : z.string().optional()",
+	);
+	assert_eq!(
+		zod_validator(&declaration(VariableType::Enum { values: vec!["a".to_owned(), "b".to_owned()] }, Required::Always(true))),
+		"z.enum([\"a\", \"b\"])",
+	);
+	assert!(zod_type(&VariableType::Base64Key { length: 32 }).ends_with(".length(44)"));
+}
+
+#[test]
+fn can_collect_managed_variables() {
+	let variables = collect_variables(vec!["DATABASE_URL".to_owned(), constants::ENV_VARIABLE__NEXTAUTH_SECRET.to_owned()], &EnvSchema::default());
+
+	assert_eq!(
+		variables.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+		vec![
+			"DATABASE_URL",
+			constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		],
+	);
+	assert_eq!(variables[0].1.kind, VariableType::String);
+	assert_eq!(variables[1].1.kind, VariableType::Base64Key { length: MANAGED_KEY_LENGTH });
+}
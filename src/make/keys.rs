@@ -1,66 +1,163 @@
-use std::io::{Seek, Write};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 use alkali::{asymmetric::kx, symmetric::cipher};
 use anyhow::Context;
-use clap::Args;
+use clap::{Parser, Subcommand, ValueEnum};
 use log::{debug, info, trace, warn};
 
 use structures::{
 	environment_record::EnvironmentRecord,
 	environment_variables::EnvironmentVariables,
+	json_web_key::{JsonWebKey, JsonWebKeySet, OctetKeyPairCurve},
 };
 
+use crate::env::env_file;
+use crate::error::CompanionError;
+use crate::events;
 use crate::global_args;
-use crate::helpers::base64_url;
+use crate::helpers::{base64_url, base64_url_decode};
 use crate::make::keys::structures::environment_variables::ENVIRONMENT_VARIABLES_KEYS;
+use crate::redaction;
 use crate::structures::file_mode::FileMode;
-use crate::structures::stream_reader::StreamReader;
+use crate::structures::stream_writer::StreamWriter;
 
 pub mod constants;
+mod derivation;
+mod inspect;
+mod output;
+mod rotation;
 mod structures;
 mod table;
-
-#[derive(Args, Debug)]
-pub struct KeysArgs {
-	/// File to read the environment variables from, starting from the current working directory
-	#[arg(long, short, default_value = ".env")]
-	env: PathBuf,
+pub(crate) mod verify;
+
+/// Format used to display the generated keys
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum KeysFormat {
+	/// Table of environment variables (or log record when using json)
+	Table,
+	/// JSON Web Key Set (RFC 7517) of the asymmetric keys
+	Jwk,
 }
 
-/// Check if the line should be updated
-fn should_update_line(line: &str, environment_variable: &EnvironmentRecord) -> bool {
-	line.starts_with(environment_variable.name()) && !environment_variable.updated()
+#[derive(Subcommand, Debug)]
+enum KeysSubCommand {
+	/// Rotate the keys, moving the current values to the previous generation variables
+	#[command()]
+	Rotate(rotation::RotateArgs),
+
+	/// Restore the previous generation of the keys
+	#[command()]
+	Rollback(rotation::RotateArgs),
+
+	/// Print the kind, length, fingerprint and keypair consistency of the stored keys, without revealing them
+	#[command()]
+	Inspect,
 }
 
-/// Update the line with the new value
-fn update_line(content_store: &mut String, environment_variable: &mut EnvironmentRecord) {
-	content_store.push_str(&*format!("{}=\"{}\"\n", environment_variable.name(), environment_variable.value()));
-	environment_variable.set_as_updated();
+#[derive(Parser, Debug)]
+pub struct KeysArgs {
+	#[command(subcommand)]
+	command: Option<KeysSubCommand>,
+
+	/// Files to read the environment variables from, starting from the current working directory,
+	/// repeat the option or separate the paths with commas, globs such as `apps/*/.env` are
+	/// expanded and every file gets the same values, `-` reads stdin and writes the updated file
+	/// to stdout
+	#[arg(long, short, default_value = ".env", global = true, value_delimiter = ',')]
+	env: Vec<PathBuf>,
+
+	/// Format used to display the generated keys
+	#[arg(long, value_enum, default_value_t = KeysFormat::Table)]
+	format: KeysFormat,
+
+	/// Write the asymmetric keys as a JSON Web Key Set to the given file
+	#[arg(long)]
+	jwks_out: Option<PathBuf>,
+
+	/// Include the private keys in the JSON Web Keys
+	#[arg(long)]
+	jwk_include_private: bool,
+
+	/// Only regenerate the given environment variables, the asymmetric keypair is always regenerated as a whole
+	#[arg(
+		long,
+		global = true,
+		value_delimiter = ',',
+		value_parser = [
+			constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		]
+	)]
+	only: Vec<String>,
+
+	/// Only fill in missing or placeholder values, leaving already populated keys intact
+	#[arg(long)]
+	skip_existing: bool,
+
+	/// Derive the keys from the base64 url encoded master secret stored in the given environment
+	/// variable instead of generating random ones
+	#[arg(long, value_name = "ENV_VARIABLE", requires = "derive_context")]
+	derive_from: Option<String>,
+
+	/// Context label (e.g. the preview environment name) the derived keys are bound to
+	#[arg(long, value_name = "LABEL", requires = "derive_from")]
+	derive_context: Option<String>,
+
+	/// Verify the keys stored in the environment file instead of generating new ones
+	#[arg(long, conflicts_with_all = ["only", "skip_existing", "jwks_out", "derive_from"])]
+	verify: bool,
+
+	/// Show the secret keys in clear instead of masking them in the table and logs
+	#[arg(long)]
+	reveal: bool,
+
+	/// Write the generated keys to stdout in a machine readable format, logging to stderr instead
+	#[arg(long, value_enum, conflicts_with_all = ["format", "verify"])]
+	output: Option<output::KeysOutput>,
+
+	/// Name of the secret when using the kubernetes-secret output
+	#[arg(long, default_value = "saas-template-keys")]
+	kubernetes_secret_name: String,
+
+	/// Restrict the environment files readable by other users to their owner instead of warning
+	#[arg(long, global = true)]
+	fix_permissions: bool,
+
+	/// Refuse to write environment files into directories not owned by the current user
+	#[arg(long, global = true)]
+	require_owned_directory: bool,
 }
 
-/// Store or update the current .env file line
-fn store_or_update_line(content_store: &mut String, line: &str, environment_variables: &mut EnvironmentVariables) {
-	if should_update_line(&line, &environment_variables.next_auth_secret) {
-		update_line(
-			content_store,
-			&mut environment_variables.next_auth_secret,
-		)
-	} else if should_update_line(&line, &environment_variables.asymmetric_encryption_public_key) {
-		update_line(
-			content_store,
-			&mut environment_variables.asymmetric_encryption_public_key,
-		)
-	} else if should_update_line(&line, &environment_variables.asymmetric_encryption_private_key) {
-		update_line(
-			content_store,
-			&mut environment_variables.asymmetric_encryption_private_key,
-		)
-	} else {
-		content_store.push_str(&*line);
+impl KeysArgs {
+	/// Whether stdout is reserved for the generated keys or the updated environment file
+	pub fn writes_data_to_stdout(&self) -> bool {
+		self.writes_env_to_stdout() || (self.command.is_none() && self.output.is_some())
+	}
+
+	/// Whether the environment file is read from stdin and written back to stdout
+	fn writes_env_to_stdout(&self) -> bool {
+		let updates_env = !self.verify && !matches!(self.command, Some(KeysSubCommand::Inspect));
+		updates_env && self.env.iter().any(|env| env_file::is_stdio(env))
+	}
+
+	/// Arguments of a `make keys --skip-existing` run, filling in the missing keys of an environment file
+	pub fn fill_in(env: &Path) -> anyhow::Result<Self> {
+		let arguments = [OsStr::new("keys"), OsStr::new("--skip-existing"), OsStr::new("--env"), env.as_os_str()];
+
+		Self::try_parse_from(arguments)
+			.with_context(|| format!("Something went wrong while building the arguments to fill in {}", env.display()))
 	}
 }
 
+/// Environment variables generated together as the asymmetric keypair
+const KEYPAIR_VARIABLES: [&str; 2] = [
+	constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+	constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+];
+
 /// Generate a new keypair
 /// # Returns
 /// A tuple with the public and private keys
@@ -89,71 +186,308 @@ fn make_secret_key() -> anyhow::Result<String> {
 }
 
 /// Print the environment variables as a table or JSON
-fn print_datatable(is_json_context: bool, environment_variables: &EnvironmentVariables) {
+fn print_datatable(is_json_context: bool, environment_variables: &EnvironmentVariables, reveal: bool) -> anyhow::Result<()> {
 	if !is_json_context {
 		info!("Encryption keys created successfully");
-		table::display_environment_variables_table(&environment_variables);
+		table::display_environment_variables_table(environment_variables, reveal);
 	} else {
-		log_mdc::insert("variables", environment_variables.clone());
+		events::insert_json("variables", environment_variables)?;
 		info!("Encryption keys created successfully");
 	}
+
+	Ok(())
 }
 
-/// Store the updated .env content into the file
-fn store_updated_env_file(stream_reader: &mut StreamReader, updated_content: &str) -> anyhow::Result<()> {
-	stream_reader.file()
-	             .rewind()
-	             .with_context(|| "Cannot move cursor back to file start, does the file exist and allows reading?")?;
-	stream_reader.file()
-	             .write_all(updated_content.as_bytes())
-	             .with_context(|| "Cannot write to file, does the file exist and allows writing?")?;
+/// Build the JSON Web Key Set of the asymmetric keys, either generated or already stored in the
+/// environment file
+fn make_json_web_key_set(public_key: &str, private_key: &str, include_private: bool) -> anyhow::Result<JsonWebKeySet> {
+	let mut required = vec![(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY, public_key)];
+	if include_private {
+		required.push((constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY, private_key));
+	}
+	if let Some((name, _)) = required.iter().find(|(_, value)| env_file::is_placeholder(Some(value))) {
+		anyhow::bail!(CompanionError::Validation(format!(
+			"{} is not set, run `make keys` without --only to generate the asymmetric keypair",
+			name,
+		)));
+	}
+
+	let public_key = base64_url_decode(public_key)
+		.with_context(|| "Something went wrong while decoding public key")?;
+	let private_key = if include_private {
+		Some(
+			base64_url_decode(private_key)
+				.with_context(|| "Something went wrong while decoding private key")?
+		)
+	} else {
+		None
+	};
 
-	Ok(())
+	let key = JsonWebKey::new(OctetKeyPairCurve::X25519, &public_key, private_key.as_deref())?;
+	debug!("Created JSON Web Key with kid {}", key.kid());
+
+	let mut json_web_key_set = JsonWebKeySet::default();
+	json_web_key_set.push(key);
+
+	Ok(json_web_key_set)
 }
 
-/// Ensure that the variable exists or add it to the content store
-fn ensure_variables_exists_or_add(content_store: &mut String, environment_variable: &mut EnvironmentRecord) {
-	if !content_store.contains(environment_variable.name()) {
-		content_store.push_str(&*format!("{}=\"{}\"\n", environment_variable.name(), environment_variable.value()));
-		environment_variable.set_as_updated();
+/// Print the JSON Web Key Set, or attach it to the result when using json
+fn print_json_web_key_set(is_json_context: bool, json_web_key_set: &JsonWebKeySet) -> anyhow::Result<()> {
+	if is_json_context {
+		events::insert_json("jwks", json_web_key_set)?;
+	} else {
+		println!(
+			"{}",
+			serde_json::to_string_pretty(json_web_key_set).with_context(|| "Cannot serialize JSON Web Key Set")?
+		);
 	}
+
+	Ok(())
+}
+
+/// Store the JSON Web Key Set into the given file
+fn store_json_web_key_set(json_web_key_set: &JsonWebKeySet, path: &Path) -> anyhow::Result<()> {
+	info!("Writing JSON Web Key Set");
+
+	let jwks = path.to_str().ok_or(anyhow::anyhow!("Cannot convert JSON Web Key Set file path to string"))?;
+
+	let mut stream_writer = StreamWriter::new(
+		jwks,
+		FileMode::builder().write().create().truncate().private().build()?,
+	).with_context(|| format!("Something went wrong while opening stream writer to {}", jwks))?;
+
+	let content = serde_json::to_string_pretty(json_web_key_set).with_context(|| "Cannot serialize JSON Web Key Set")?;
+	stream_writer.write_all(content.as_bytes())?;
+
+	info!("JSON Web Key Set written to {}", jwks);
+
+	Ok(())
 }
 
-/// Update the .env file with the new values
-fn update_env(environment_variables: &mut EnvironmentVariables, arguments: &KeysArgs) -> anyhow::Result<()> {
+/// Update the .env files with the new values, reporting which ones were created and updated
+fn update_env(environment_variables: &mut EnvironmentVariables, env_files: &[PathBuf], arguments: &KeysArgs) -> anyhow::Result<()> {
 	info!("Updating .env file");
 
-	let env = arguments.env.to_str().ok_or(anyhow::anyhow!("Cannot convert environment file path to string"))?;
+	let values: Vec<(String, String)> = ENVIRONMENT_VARIABLES_KEYS.iter()
+	                                                              .map(|key| &environment_variables[key])
+	                                                              .filter(|environment_variable| !environment_variable.skipped())
+	                                                              .map(|environment_variable| (
+		                                                              environment_variable.name().to_owned(),
+		                                                              environment_variable.value().to_owned(),
+	                                                              ))
+	                                                              .collect();
+
+	let mut created = Vec::new();
+	let mut updated = Vec::new();
+
+	for env in env_files {
+		let exists = env_file::is_stdio(env) || env.exists();
+		env_file::protect_secret_file(env, arguments.fix_permissions, arguments.require_owned_directory)?;
+		env_file::update_values(env, &values)
+			.with_context(|| format!("Something went wrong while updating the {} file", env.display()))?;
+
+		if exists {
+			info!("{} updated", env.display());
+			updated.push(env.display().to_string());
+		} else {
+			info!("{} created", env.display());
+			created.push(env.display().to_string());
+		}
+	}
+
+	for environment_variable_name in ENVIRONMENT_VARIABLES_KEYS {
+		let environment_variable = &mut environment_variables[environment_variable_name];
+
+		if !environment_variable.skipped() {
+			environment_variable.set_as_updated();
+		}
+	}
+
+	events::insert_json("created", &created)?;
+	events::insert_json("updated", &updated)?;
+	info!(".env file update completed, {} created, {} updated", created.len(), updated.len());
+
+	Ok(())
+}
 
-	let mut stream_reader = StreamReader::new(
-		env,
-		FileMode::builder().write().read().create().build(),
-	).with_context(|| format!("Something went wrong while opening stream reader to {}", env))?;
+/// Resolve the environment variables to regenerate, when skipping the existing values a missing
+/// public key is recovered from the private key and a lone public key cannot be completed
+pub(crate) fn select_variables(only: &[String], skip_existing: bool, existing_values: &HashMap<String, String>) -> anyhow::Result<Vec<&'static str>> {
+	let requested = |name: &str| only.is_empty() || only.iter().any(|only| only == name);
+	let is_missing = |name: &str| !skip_existing || env_file::is_placeholder(existing_values.get(name).map(String::as_str));
 
-	let mut updated_content = String::new();
+	let mut selected = Vec::new();
 
-	// read line by line and immediately store the updated content
-	loop {
-		let line = stream_reader.read_line()
-		                        .with_context(|| "Something went wrong while reading a new file line")?;
-		debug!("Found line with length of {} bytes", line.length());
+	if requested(constants::ENV_VARIABLE__NEXTAUTH_SECRET) && is_missing(constants::ENV_VARIABLE__NEXTAUTH_SECRET) {
+		selected.push(constants::ENV_VARIABLE__NEXTAUTH_SECRET);
+	}
 
-		if line.eof() {
-			debug!("EOF reached at {} file", env);
-			break;
+	if KEYPAIR_VARIABLES.iter().any(|name| requested(name)) {
+		if !KEYPAIR_VARIABLES.iter().all(|name| requested(name)) {
+			warn!("The asymmetric keypair can only be regenerated as a whole, both public and private keys will be updated");
 		}
 
-		store_or_update_line(&mut updated_content, line.line(), environment_variables);
+		match (
+			is_missing(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY),
+			is_missing(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY),
+		) {
+			(true, true) => selected.extend(KEYPAIR_VARIABLES),
+			(true, false) => selected.push(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY),
+			(false, true) => anyhow::bail!(CompanionError::Validation(format!(
+				"{} is missing while {} is set, the private key cannot be recovered, remove the public key to generate a new keypair",
+				constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+				constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+			))),
+			(false, false) => {}
+		}
 	}
 
-	for environment_variable_name in ENVIRONMENT_VARIABLES_KEYS {
-		ensure_variables_exists_or_add(&mut updated_content, &mut environment_variables[environment_variable_name]);
+	Ok(selected)
+}
+
+/// Resolve the environment variables to regenerate in every file, failing when the files need
+/// different keys since they all get the same values
+fn select_shared_variables(
+	env_files: &[PathBuf],
+	files_existing_values: &[HashMap<String, String>],
+	arguments: &KeysArgs,
+) -> anyhow::Result<Vec<&'static str>> {
+	let mut selections = Vec::new();
+	for (env, existing_values) in env_files.iter().zip(files_existing_values) {
+		selections.push(
+			select_variables(&arguments.only, arguments.skip_existing, existing_values)
+				.with_context(|| format!("Cannot select the keys to generate in {}", env.display()))?
+		);
 	}
 
-	store_updated_env_file(&mut stream_reader, &updated_content)
-		.with_context(|| format!("Something went wrong while updating the {} file", env))?;
+	if selections.iter().any(|selected| selected != &selections[0]) {
+		let details: Vec<String> = env_files.iter()
+		                                    .zip(&selections)
+		                                    .map(|(env, selected)| {
+			                                    let selected = if selected.is_empty() { "nothing".to_owned() } else { selected.join(", ") };
+			                                    format!("{} needs {}", env.display(), selected)
+		                                    })
+		                                    .collect();
+		anyhow::bail!(CompanionError::Validation(format!(
+			"The environment files are missing different keys ({}), fill them in one file at a time",
+			details.join("; "),
+		)));
+	}
+
+	// the public key is recovered once from the private key of the first file
+	let selected = selections.swap_remove(0);
+	let recovers_public_key = selected.contains(&constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY) &&
+		!selected.contains(&constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY);
+	let private_key = |existing_values: &HashMap<String, String>| existing_values.get(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY).cloned();
+	if recovers_public_key && files_existing_values.iter().any(|existing_values| private_key(existing_values) != private_key(&files_existing_values[0])) {
+		anyhow::bail!(CompanionError::Validation(format!(
+			"The environment files hold different {} values, fill them in one file at a time",
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		)));
+	}
+
+	Ok(selected)
+}
+
+/// Generate the new values of the selected environment variables, the keypair is only generated
+/// when its private key is selected
+pub(crate) fn generate_values(selected: &[&'static str]) -> anyhow::Result<HashMap<&'static str, String>> {
+	let mut values = HashMap::new();
+
+	if selected.contains(&constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY) {
+		info!("Generating asymmetric encryption keys");
+		let (b64_public, b64_secret) = make_keypair().with_context(|| "Something went wrong during asymmetric keypair creation")?;
+
+		values.insert(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY, b64_public);
+		values.insert(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY, b64_secret);
+	}
+
+	if selected.contains(&constants::ENV_VARIABLE__NEXTAUTH_SECRET) {
+		info!("Generating symmetric encryption keys");
+		let b64_key = make_secret_key().with_context(|| "Something went wrong during symmetric key creation")?;
+
+		values.insert(constants::ENV_VARIABLE__NEXTAUTH_SECRET, b64_key);
+	}
+
+	Ok(values)
+}
+
+/// Recover the public key from the existing private key when only the public key is selected
+pub(crate) fn recover_public_key(
+	selected: &[&'static str],
+	existing_values: &HashMap<String, String>,
+	values: &mut HashMap<&'static str, String>,
+) -> anyhow::Result<()> {
+	if !selected.contains(&constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY) ||
+		selected.contains(&constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY) {
+		return Ok(());
+	}
+
+	info!("Recovering the asymmetric public key from the private key");
+	let private_key = existing_values.get(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY)
+	                                 .map(String::as_str)
+	                                 .unwrap_or_default();
+	let private_key = kx::PrivateKey::try_from(
+		base64_url_decode(private_key).with_context(|| "Something went wrong while decoding private key")?.as_slice()
+	).with_context(|| format!("{} is not a valid private key", constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY))?;
+	let keypair = kx::Keypair::from_private_key(&private_key)
+		.with_context(|| "Something went wrong while deriving the public key")?;
+
+	values.insert(
+		constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		base64_url(keypair.public_key.as_slice()).with_context(|| "Something went wrong while encoding public key")?,
+	);
+
+	Ok(())
+}
+
+/// Create the record of a variable, keeping the existing value if it should not be regenerated
+fn make_record<'a>(
+	name: &'a str,
+	generated_value: &'a str,
+	selected: &[&str],
+	existing_values: &'a HashMap<String, String>,
+) -> EnvironmentRecord<'a> {
+	if selected.contains(&name) {
+		return EnvironmentRecord::new(name, generated_value);
+	}
+
+	debug!("Skipping {}", name);
+	let mut record = EnvironmentRecord::new(name, existing_values.get(name).map(String::as_str).unwrap_or_default());
+	record.set_as_skipped();
+
+	record
+}
+
+/// Print or store the JSON Web Key Set of the keypair already stored in the environment file, when
+/// there is nothing to generate
+fn export_json_web_key_set(
+	global_arguments: &global_args::GlobalArgs,
+	arguments: &KeysArgs,
+	existing_values: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+	if arguments.format != KeysFormat::Jwk && arguments.jwks_out.is_none() {
+		return Ok(());
+	}
 
-	info!(".env file update completed");
+	let existing_value = |name: &str| existing_values.get(name).map(String::as_str).unwrap_or_default();
+	let json_web_key_set = make_json_web_key_set(
+		existing_value(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY),
+		existing_value(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY),
+		arguments.jwk_include_private,
+	).with_context(|| "Something went wrong during JSON Web Key Set creation")?;
+
+	if arguments.format == KeysFormat::Jwk && arguments.output.is_none() && !arguments.writes_env_to_stdout() {
+		print_json_web_key_set(global_arguments.json, &json_web_key_set)?;
+	}
+
+	match &arguments.jwks_out {
+		Some(_) if global_arguments.dry_run => warn!("Dry run, skipping file update"),
+		Some(path) => store_json_web_key_set(&json_web_key_set, path)
+			.with_context(|| "Something went wrong while storing the JSON Web Key Set")?,
+		None => {}
+	}
 
 	Ok(())
 }
@@ -161,31 +495,122 @@ fn update_env(environment_variables: &mut EnvironmentVariables, arguments: &Keys
 pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &KeysArgs) -> anyhow::Result<()> {
 	trace!("{:?}", global_arguments);
 
-	info!("Generating asymmetric encryption keys");
-	let (b64_public, b64_secret) = make_keypair().with_context(|| "Something went wrong during asymmetric keypair creation")?;
+	match &arguments.command {
+		Some(KeysSubCommand::Rotate(options)) => {
+			return rotation::rotate(global_arguments, arguments, options);
+		}
+		Some(KeysSubCommand::Rollback(options)) => {
+			return rotation::rollback(global_arguments, arguments, options);
+		}
+		Some(KeysSubCommand::Inspect) => {
+			return inspect::inspect(global_arguments, arguments);
+		}
+		None if arguments.verify => {
+			return verify::verify(global_arguments, arguments);
+		}
+		None => {}
+	}
+
+	if arguments.writes_env_to_stdout() && arguments.output.is_some() {
+		anyhow::bail!(CompanionError::Config(
+			"Cannot write both the generated keys and the environment file to stdout, use --env with a file or drop --output".to_owned()
+		));
+	}
+
+	// the keys to generate are selected in every file, all the files then get the same values
+	let env_files = env_file::resolve_paths(&arguments.env)?;
+	let mut files_existing_values = Vec::new();
+	for env in &env_files {
+		files_existing_values.push(
+			env_file::read_values(env)
+				.with_context(|| format!("Something went wrong while reading the current environment variables of {}", env.display()))?
+		);
+	}
+	let selected = select_shared_variables(&env_files, &files_existing_values, arguments)?;
+	let existing_values = files_existing_values.swap_remove(0);
+
+	if selected.is_empty() {
+		info!("All encryption keys are already set, nothing to generate");
+		return export_json_web_key_set(global_arguments, arguments, &existing_values);
+	}
 
-	info!("Generating symmetric encryption keys");
-	let b64_key = make_secret_key().with_context(|| "Something went wrong during symmetric key creation")?;
+	let mut generated_values = match (&arguments.derive_from, &arguments.derive_context) {
+		(Some(master_secret_env), Some(context_label)) => {
+			derivation::derive_values(&selected, master_secret_env, context_label)
+				.with_context(|| "Something went wrong during key derivation")?
+		}
+		_ => generate_values(&selected)?,
+	};
+	recover_public_key(&selected, &existing_values, &mut generated_values)?;
+	if !arguments.reveal {
+		generated_values.iter()
+		                .filter(|(name, _)| constants::SECRET_ENV_VARIABLES.contains(name))
+		                .for_each(|(_, value)| redaction::register_secret(value));
+	}
+	let generated_value = |name: &str| generated_values.get(name).map(String::as_str).unwrap_or_default();
 
 	let mut environment_variables = EnvironmentVariables {
-		next_auth_secret: EnvironmentRecord::new(
+		next_auth_secret: make_record(
 			constants::ENV_VARIABLE__NEXTAUTH_SECRET,
-			b64_key.as_str(),
+			generated_value(constants::ENV_VARIABLE__NEXTAUTH_SECRET),
+			&selected,
+			&existing_values,
 		),
-		asymmetric_encryption_public_key: EnvironmentRecord::new(
+		asymmetric_encryption_public_key: make_record(
 			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
-			b64_public.as_str(),
+			generated_value(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY),
+			&selected,
+			&existing_values,
 		),
-		asymmetric_encryption_private_key: EnvironmentRecord::new(
+		asymmetric_encryption_private_key: make_record(
 			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
-			b64_secret.as_str(),
+			generated_value(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY),
+			&selected,
+			&existing_values,
 		),
 	};
 
-	print_datatable(global_arguments.json, &environment_variables);
+	let json_web_key_set = if arguments.format == KeysFormat::Jwk || arguments.jwks_out.is_some() {
+		Some(
+			make_json_web_key_set(
+				environment_variables.asymmetric_encryption_public_key.value(),
+				environment_variables.asymmetric_encryption_private_key.value(),
+				arguments.jwk_include_private,
+			).with_context(|| "Something went wrong during JSON Web Key Set creation")?
+		)
+	} else {
+		None
+	};
+
+	match (arguments.output, &arguments.format, &json_web_key_set) {
+		(Some(output), _, _) => {
+			let variables: Vec<(&str, &str)> = ENVIRONMENT_VARIABLES_KEYS.iter()
+			                                                             .map(|key| &environment_variables[key])
+			                                                             .filter(|record| !record.skipped())
+			                                                             .map(|record| (record.name(), record.value()))
+			                                                             .collect();
+			output::print(output, &variables, &arguments.kubernetes_secret_name, global_arguments.dry_run)?;
+		}
+		// stdout only carries the updated environment file
+		(None, _, _) if arguments.writes_env_to_stdout() => info!("Encryption keys created successfully"),
+		(None, KeysFormat::Jwk, Some(json_web_key_set)) => {
+			info!("Encryption keys created successfully");
+			print_json_web_key_set(global_arguments.json, json_web_key_set)?
+		}
+		_ => print_datatable(global_arguments.json, &environment_variables, arguments.reveal)?,
+	}
 
 	if !global_arguments.dry_run {
-		update_env(&mut environment_variables, arguments).with_context(|| "Something went wrong while updating the environment file")?;
+		update_env(&mut environment_variables, &env_files, arguments).with_context(|| "Something went wrong while updating the environment file")?;
+		if global_arguments.json {
+			// the result reflects which variables were actually updated
+			events::insert_json("variables", &environment_variables)?;
+		}
+
+		if let (Some(path), Some(json_web_key_set)) = (&arguments.jwks_out, &json_web_key_set) {
+			store_json_web_key_set(json_web_key_set, path)
+				.with_context(|| "Something went wrong while storing the JSON Web Key Set")?;
+		}
 	} else {
 		warn!("Dry run, skipping file update");
 	}
@@ -1,3 +1,14 @@
 pub const ENV_VARIABLE__NEXTAUTH_SECRET: &str = "NEXTAUTH_SECRET";
 pub const ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY: &str = "ASYMMETRIC_ENCRYPTION_PUBLIC_KEY";
-pub const ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY: &str = "ASYMMETRIC_ENCRYPTION_PRIVATE_KEY";
\ No newline at end of file
+pub const ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY: &str = "ASYMMETRIC_ENCRYPTION_PRIVATE_KEY";
+/// Environment variables managed (generated and stored) by `make keys`
+pub const MANAGED_ENV_VARIABLES: [&str; 3] = [
+	ENV_VARIABLE__NEXTAUTH_SECRET,
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+];
+/// Managed environment variables holding secrets, masked in the outputs unless revealed
+pub const SECRET_ENV_VARIABLES: [&str; 2] = [
+	ENV_VARIABLE__NEXTAUTH_SECRET,
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+];
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use alkali::asymmetric::kx;
+use alkali::symmetric::cipher;
+use alkali::hash::{generic, kdf};
+use anyhow::Context;
+use log::info;
+
+use crate::error::CompanionError;
+use crate::helpers::{base64_url, base64_url_decode};
+use crate::make::keys::constants;
+use crate::redaction;
+
+/// Libsodium KDF context shared by all the derived keys, must be exactly 8 bytes long
+const KEY_DERIVATION_CONTEXT: &[u8; kdf::CONTEXT_LENGTH] = b"saastmpl";
+
+/// Subkey identifier of the symmetric key, must never change or derived keys will differ
+const SUBKEY_ID__NEXTAUTH_SECRET: u64 = 1;
+
+/// Subkey identifier of the asymmetric keypair seed, must never change or derived keys will differ
+const SUBKEY_ID__ASYMMETRIC_ENCRYPTION_KEYPAIR: u64 = 2;
+
+/// Load the master secret from the process environment variable with the given name
+fn load_master_key(master_secret_env: &str) -> anyhow::Result<Vec<u8>> {
+	let encoded_key = std::env::var(master_secret_env)
+		.with_context(|| format!("Cannot read the master secret from the {} environment variable", master_secret_env))?;
+	redaction::register_secret(&encoded_key);
+	let key = base64_url_decode(&encoded_key)
+		.with_context(|| "Something went wrong while decoding the master secret, is it base64 url encoded?")?;
+
+	if key.len() != kdf::KEY_LENGTH {
+		anyhow::bail!(CompanionError::Config(format!("The master secret must be {} bytes long, found {} bytes", kdf::KEY_LENGTH, key.len())));
+	}
+
+	Ok(key)
+}
+
+/// Bind the master secret to the context label, so that every label gets an independent set of keys
+fn make_context_key(master_key: &[u8], context_label: &str) -> anyhow::Result<kdf::Key<alkali::mem::FullAccess>> {
+	let context_key = generic::hash_custom_to_vec(context_label.as_bytes(), Some(master_key), kdf::KEY_LENGTH)
+		.with_context(|| "Something went wrong while binding the master secret to the context label")?;
+
+	kdf::Key::try_from(context_key.as_slice())
+		.with_context(|| "Something went wrong while loading the context key")
+}
+
+/// Derive the values of the selected environment variables from the master secret and context label
+pub fn derive_values(selected: &[&'static str], master_secret_env: &str, context_label: &str) -> anyhow::Result<HashMap<&'static str, String>> {
+	let master_key = load_master_key(master_secret_env)?;
+	let context_key = make_context_key(&master_key, context_label)?;
+
+	let mut values = HashMap::new();
+
+	if selected.contains(&constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY) {
+		info!("Deriving asymmetric encryption keys for context '{}'", context_label);
+
+		let mut seed = kx::Seed::new_empty()
+			.with_context(|| "Something went wrong while allocating the keypair seed")?;
+		kdf::derive_subkey(&context_key, KEY_DERIVATION_CONTEXT, SUBKEY_ID__ASYMMETRIC_ENCRYPTION_KEYPAIR, &mut seed[..])
+			.with_context(|| "Something went wrong while deriving the keypair seed")?;
+		let keypair = kx::Keypair::from_seed(&seed)
+			.with_context(|| "Something went wrong while deriving the asymmetric keypair")?;
+
+		values.insert(
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+			base64_url(keypair.public_key.as_slice()).with_context(|| "Something went wrong while encoding public key")?,
+		);
+		values.insert(
+			constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+			base64_url(keypair.private_key.as_slice()).with_context(|| "Something went wrong while encoding secret key")?,
+		);
+	}
+
+	if selected.contains(&constants::ENV_VARIABLE__NEXTAUTH_SECRET) {
+		info!("Deriving symmetric encryption keys for context '{}'", context_label);
+
+		let mut key = [0u8; cipher::KEY_LENGTH];
+		kdf::derive_subkey(&context_key, KEY_DERIVATION_CONTEXT, SUBKEY_ID__NEXTAUTH_SECRET, &mut key)
+			.with_context(|| "Something went wrong while deriving the symmetric key")?;
+
+		values.insert(
+			constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+			base64_url(&key).with_context(|| "Something went wrong while encoding symmetric key")?,
+		);
+	}
+
+	Ok(values)
+}
+
+#[test]
+fn can_derive_deterministic_keys() {
+	let selected = [
+		constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+		constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+	];
+	std::env::set_var("DERIVATION_TEST_MASTER_SECRET", base64_url(&[7u8; kdf::KEY_LENGTH]).unwrap());
+
+	let first = derive_values(&selected, "DERIVATION_TEST_MASTER_SECRET", "pr-42").unwrap();
+	let second = derive_values(&selected, "DERIVATION_TEST_MASTER_SECRET", "pr-42").unwrap();
+	let other = derive_values(&selected, "DERIVATION_TEST_MASTER_SECRET", "pr-43").unwrap();
+
+	assert_eq!(first, second);
+	assert_ne!(first[constants::ENV_VARIABLE__NEXTAUTH_SECRET], other[constants::ENV_VARIABLE__NEXTAUTH_SECRET]);
+	assert_ne!(
+		first[constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY],
+		other[constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY]
+	);
+}
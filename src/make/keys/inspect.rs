@@ -0,0 +1,166 @@
+use anyhow::Context;
+use log::{info, trace, warn};
+
+use crate::env::env_file;
+use crate::events;
+use crate::global_args;
+use crate::helpers::{base64_url_decode, fingerprint};
+use crate::make::keys::structures::key_inspection::{KeyInspection, KeyInspectionReport, KeyKind};
+use crate::make::keys::verify::is_matching_keypair;
+use crate::make::keys::{constants, table, KeysArgs};
+
+/// Kind of the keys stored in each managed environment variable
+const KEY_KINDS: [(&str, KeyKind); 3] = [
+	(constants::ENV_VARIABLE__NEXTAUTH_SECRET, KeyKind::Symmetric),
+	(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY, KeyKind::X25519Public),
+	(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY, KeyKind::X25519Private),
+];
+
+/// Find the kind of key a variable holds, either a managed variable itself or one of its previous
+/// generations (e.g. `NEXTAUTH_SECRET_PREVIOUS`), along with the generation suffix
+fn managed_variable(name: &str) -> Option<(KeyKind, &str)> {
+	KEY_KINDS.iter()
+	         .find_map(|(managed_name, kind)| {
+		         name.strip_prefix(managed_name)
+		             .filter(|suffix| suffix.is_empty() || suffix.starts_with('_'))
+		             .map(|suffix| (*kind, suffix))
+	         })
+}
+
+/// Inspect all the managed keys, and their previous generations, stored in an environment file
+pub fn inspect_values(env: &str, entries: &[(String, String)]) -> anyhow::Result<Vec<KeyInspection>> {
+	let mut results = Vec::new();
+	let mut decoded_keys = Vec::new();
+
+	for (name, value) in entries {
+		let Some((kind, suffix)) = managed_variable(name) else {
+			continue;
+		};
+
+		if env_file::is_placeholder(Some(value)) {
+			results.push(KeyInspection::new(env, name, kind, "Variable is empty or holds a placeholder"));
+			continue;
+		}
+
+		match base64_url_decode(value) {
+			Ok(key) => {
+				let mut inspection = KeyInspection::new(env, name, kind, "");
+				inspection.set_decoded(
+					key.len(),
+					fingerprint(value).with_context(|| format!("Something went wrong while fingerprinting {}", name))?,
+				);
+				results.push(inspection);
+				decoded_keys.push((kind, suffix.to_owned(), key));
+			}
+			Err(_) => results.push(KeyInspection::new(env, name, kind, "Value is not base64 url encoded")),
+		}
+	}
+
+	// pair the halves of each generation of the keypair
+	for (_, suffix, private_key) in decoded_keys.iter().filter(|(kind, _, _)| *kind == KeyKind::X25519Private) {
+		let public_key = decoded_keys.iter()
+		                             .find(|(other_kind, other_suffix, _)| *other_kind == KeyKind::X25519Public && other_suffix == suffix)
+		                             .map(|(_, _, key)| key);
+
+		if let Some(public_key) = public_key {
+			// a key with the wrong length cannot be loaded, hence cannot match
+			let keypair_match = is_matching_keypair(public_key, private_key).unwrap_or(false);
+
+			results.iter_mut()
+			       .filter(|result| result.length().is_some())
+			       .filter(|result| result.kind() != KeyKind::Symmetric)
+			       .filter(|result| managed_variable(result.name()).is_some_and(|(_, other_suffix)| other_suffix == suffix))
+			       .for_each(|result| result.set_keypair_match(keypair_match));
+		}
+	}
+
+	Ok(results)
+}
+
+/// Warn about the keys sharing the same value across different environment files
+fn warn_shared_keys(report: &KeyInspectionReport) {
+	for (index, result) in report.results.iter().enumerate() {
+		let Some(fingerprint) = result.fingerprint() else {
+			continue;
+		};
+
+		let shared_with = report.results[index + 1..].iter().find(|other| {
+			other.env_file() != result.env_file() && other.kind() == result.kind() && other.fingerprint() == Some(fingerprint)
+		});
+
+		if let Some(other) = shared_with {
+			warn!(
+				"{} in {} and {} in {} share the same value ({})",
+				result.name(),
+				result.env_file(),
+				other.name(),
+				other.env_file(),
+				fingerprint,
+			);
+		}
+	}
+}
+
+/// Print the inventory of the keys stored in the environment files, without revealing them
+pub fn inspect(global_arguments: &global_args::GlobalArgs, arguments: &KeysArgs) -> anyhow::Result<()> {
+	trace!("{:?}", arguments);
+
+	let mut report = KeyInspectionReport::default();
+
+	for env in env_file::resolve_paths(&arguments.env)? {
+		if !env_file::is_stdio(&env) && !env.exists() {
+			warn!("{} does not exist", env.display());
+			continue;
+		}
+
+		let entries = env_file::read_entries(&env)
+			.with_context(|| format!("Something went wrong while reading {}", env.display()))?;
+		report.results.extend(inspect_values(&env.display().to_string(), &entries)?);
+	}
+
+	if !global_arguments.json {
+		table::display_key_inspection_table(&report);
+	} else {
+		events::insert_json("inspection", &report)?;
+	}
+
+	warn_shared_keys(&report);
+	info!("{} keys inspected", report.results.len());
+
+	Ok(())
+}
+
+#[test]
+fn can_inspect_keys_with_generations() {
+	use alkali::asymmetric::kx;
+
+	let private_key = kx::PrivateKey::try_from([0u8; kx::PRIVATE_KEY_LENGTH].as_slice()).unwrap();
+	let public_key = crate::helpers::base64_url(kx::Keypair::from_private_key(&private_key).unwrap().public_key.as_slice()).unwrap();
+
+	let entries = vec![
+		(constants::ENV_VARIABLE__NEXTAUTH_SECRET.to_owned(), "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_owned()),
+		(format!("{}_PREVIOUS", constants::ENV_VARIABLE__NEXTAUTH_SECRET), "".to_owned()),
+		(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY.to_owned(), public_key),
+		(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY.to_owned(), "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_owned()),
+		(format!("{}_PREVIOUS", constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY), "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_owned()),
+		(format!("{}_PREVIOUS", constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY), "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".to_owned()),
+		("NEXTAUTH_URL".to_owned(), "http://localhost:3000".to_owned()),
+	];
+
+	let results = inspect_values(".env", &entries).unwrap();
+	assert_eq!(results.len(), 6);
+
+	assert_eq!(results[0].kind(), KeyKind::Symmetric);
+	assert_eq!(results[0].length(), Some(32));
+	assert_eq!(results[0].fingerprint(), results[3].fingerprint());
+	// the same fingerprint shows up in the masked values of `env list`
+	assert!(crate::helpers::mask(&entries[0].1).ends_with(&format!("#{}", results[0].fingerprint().unwrap())));
+	assert_eq!(results[0].keypair_match(), None);
+
+	assert_eq!(results[1].length(), None);
+
+	assert_eq!(results[2].keypair_match(), Some(true));
+	assert_eq!(results[3].keypair_match(), Some(true));
+	assert_eq!(results[4].keypair_match(), Some(false));
+	assert_eq!(results[5].keypair_match(), Some(false));
+}
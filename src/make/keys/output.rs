@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use clap::ValueEnum;
+use log::info;
+
+use crate::env::env_file;
+use crate::helpers::base64_standard;
+use crate::make::keys::constants;
+use crate::make::keys::structures::kubernetes_secret::KubernetesSecret;
+use crate::structures::file_mode::FileMode;
+use crate::structures::stream_writer::StreamWriter;
+
+/// Environment variable holding the path of the GitHub Actions environment file
+const GITHUB_ENV: &str = "GITHUB_ENV";
+
+/// Machine readable formats the generated keys can be written to stdout in
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum KeysOutput {
+	/// `NAME="value"` lines
+	Dotenv,
+	/// `export NAME='value'` lines, to be sourced by a POSIX shell
+	Shell,
+	/// JSON object of the values by name
+	Json,
+	/// YAML mapping of the values by name
+	Yaml,
+	/// Unquoted `NAME=value` lines, as read by `docker run --env-file`
+	DockerEnvFile,
+	/// `::add-mask::` workflow commands for the secrets, plus `NAME=value` lines appended to `$GITHUB_ENV`
+	GithubActions,
+	/// Kubernetes opaque `Secret` manifest with base64 encoded data
+	KubernetesSecret,
+}
+
+/// Quote a value for a POSIX shell
+fn shell_quote(value: &str) -> String {
+	format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Render the variables in the given format, `GithubActions` only renders the `$GITHUB_ENV` lines
+pub fn render(output: KeysOutput, variables: &[(&str, &str)], kubernetes_secret_name: &str) -> anyhow::Result<String> {
+	let content = match output {
+		KeysOutput::Dotenv => variables.iter().map(|(name, value)| env_file::format_line(name, value)).collect(),
+		KeysOutput::Shell => variables.iter().map(|(name, value)| format!("export {}={}\n", name, shell_quote(value))).collect(),
+		KeysOutput::DockerEnvFile | KeysOutput::GithubActions => {
+			variables.iter().map(|(name, value)| format!("{}={}\n", name, value)).collect()
+		}
+		KeysOutput::Json => {
+			let values: BTreeMap<&str, &str> = variables.iter().copied().collect();
+			format!("{}\n", serde_json::to_string_pretty(&values).with_context(|| "Cannot serialize the keys to JSON")?)
+		}
+		KeysOutput::Yaml => {
+			let values: BTreeMap<&str, &str> = variables.iter().copied().collect();
+			serde_yaml::to_string(&values).with_context(|| "Cannot serialize the keys to YAML")?
+		}
+		KeysOutput::KubernetesSecret => {
+			let mut data = BTreeMap::new();
+			for (name, value) in variables {
+				data.insert(
+					name.to_string(),
+					base64_standard(value.as_bytes()).with_context(|| format!("Something went wrong while encoding {}", name))?,
+				);
+			}
+			let manifest = KubernetesSecret::new(kubernetes_secret_name, data);
+			serde_yaml::to_string(&manifest).with_context(|| "Cannot serialize the Kubernetes secret")?
+		}
+	};
+
+	Ok(content)
+}
+
+/// Append the `NAME=value` lines to the GitHub Actions environment file
+fn append_github_env(path: &str, content: &str) -> anyhow::Result<()> {
+	let mut stream_writer = StreamWriter::new(
+		path,
+		FileMode::builder().append().create().build()?,
+	).with_context(|| format!("Something went wrong while opening stream writer to {}", path))?;
+
+	stream_writer.write_all(content.as_bytes())?;
+
+	Ok(())
+}
+
+/// Print the variables to stdout in the given format
+pub fn print(output: KeysOutput, variables: &[(&str, &str)], kubernetes_secret_name: &str, dry_run: bool) -> anyhow::Result<()> {
+	let content = render(output, variables, kubernetes_secret_name)?;
+
+	if output != KeysOutput::GithubActions {
+		print!("{}", content);
+		return Ok(());
+	}
+
+	// masks must be registered before the values can show up in any later step log
+	for (_, value) in variables.iter().filter(|(name, _)| constants::SECRET_ENV_VARIABLES.contains(name)) {
+		println!("::add-mask::{}", value);
+	}
+
+	match std::env::var(GITHUB_ENV) {
+		Ok(path) if !path.is_empty() && !dry_run => {
+			append_github_env(&path, &content)
+				.with_context(|| "Something went wrong while updating the GitHub Actions environment file")?;
+			info!("{} variables exported to ${}", variables.len(), GITHUB_ENV);
+		}
+		_ => print!("{}", content),
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_render_outputs() {
+	let variables = [("NEXTAUTH_SECRET", "it's"), ("ASYMMETRIC_ENCRYPTION_PUBLIC_KEY", "abc")];
+
+	assert_eq!(render(KeysOutput::Dotenv, &variables, "").unwrap(), "NEXTAUTH_SECRET=\"it's\"\nASYMMETRIC_ENCRYPTION_PUBLIC_KEY=\"abc\"\n");
+	assert_eq!(render(KeysOutput::Shell, &variables, "").unwrap(), "export NEXTAUTH_SECRET='it'\\''s'\nexport ASYMMETRIC_ENCRYPTION_PUBLIC_KEY='abc'\n");
+	assert_eq!(render(KeysOutput::DockerEnvFile, &variables, "").unwrap(), "NEXTAUTH_SECRET=it's\nASYMMETRIC_ENCRYPTION_PUBLIC_KEY=abc\n");
+	assert!(render(KeysOutput::Json, &variables, "").unwrap().contains("\"NEXTAUTH_SECRET\": \"it's\""));
+	let yaml: BTreeMap<String, String> = serde_yaml::from_str(&render(KeysOutput::Yaml, &variables, "").unwrap()).unwrap();
+	assert_eq!(yaml["NEXTAUTH_SECRET"], "it's");
+
+	let manifest = render(KeysOutput::KubernetesSecret, &variables, "app-keys").unwrap();
+	assert!(manifest.contains("apiVersion: v1"));
+	assert!(manifest.contains("kind: Secret"));
+	assert!(manifest.contains("name: app-keys"));
+	assert!(manifest.contains("type: Opaque"));
+	assert!(manifest.contains("ASYMMETRIC_ENCRYPTION_PUBLIC_KEY: YWJj"));
+}
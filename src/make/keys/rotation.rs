@@ -0,0 +1,276 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use alkali::symmetric::cipher;
+use anyhow::Context;
+use clap::Args;
+use log::{info, trace, warn};
+
+use crate::env::env_file;
+use crate::error::CompanionError;
+use crate::global_args;
+use crate::helpers::{base64_url, base64_url_decode};
+use crate::make::keys::structures::rotation_history_entry::{EncryptedRotationHistoryEntry, RotationHistoryEntry};
+use crate::make::keys::{generate_values, select_variables, KeysArgs};
+use crate::redaction;
+use crate::structures::file_mode::FileMode;
+use crate::structures::stream_writer::StreamWriter;
+
+/// Values to store in a file after a rotation or rollback, along with the replaced values
+type RotationValues = (Vec<(String, String)>, BTreeMap<String, String>);
+
+#[derive(Args, Debug)]
+pub struct RotateArgs {
+	/// Suffix appended to the variable name to store its previous generation
+	#[arg(long, default_value = "_PREVIOUS")]
+	previous_suffix: String,
+
+	/// Number of previous generations to keep, numbered variables (`<NAME>_PREVIOUS_<N>`) are used
+	/// when keeping more than one
+	#[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..))]
+	generations: u8,
+
+	/// Append an encrypted entry to the given rotation history file
+	#[arg(long)]
+	history: Option<PathBuf>,
+
+	/// Environment variable holding the base64 url encoded key used to encrypt the rotation history
+	#[arg(long, default_value = "SAAS_COMPANION_HISTORY_KEY")]
+	history_key_env: String,
+}
+
+/// Get the name of the variable storing the given previous generation of a variable
+fn previous_variable_name(name: &str, arguments: &RotateArgs, generation: u8) -> String {
+	if arguments.generations == 1 {
+		format!("{}{}", name, arguments.previous_suffix)
+	} else {
+		format!("{}{}_{}", name, arguments.previous_suffix, generation)
+	}
+}
+
+/// Get the current value of a variable, or an empty string if it is not defined
+fn current_value(existing_values: &HashMap<String, String>, name: &str) -> String {
+	existing_values.get(name).cloned().unwrap_or_default()
+}
+
+/// Load the rotation history encryption key from the process environment
+fn load_history_key(arguments: &RotateArgs) -> anyhow::Result<cipher::Key<alkali::mem::FullAccess>> {
+	let encoded_key = std::env::var(&arguments.history_key_env)
+		.with_context(|| format!("Cannot read the rotation history key from the {} environment variable", arguments.history_key_env))?;
+	redaction::register_secret(&encoded_key);
+	let key = base64_url_decode(&encoded_key)
+		.with_context(|| "Something went wrong while decoding the rotation history key")?;
+
+	cipher::Key::try_from(key.as_slice())
+		.with_context(|| format!("The rotation history key must be {} bytes long", cipher::KEY_LENGTH))
+}
+
+/// Load the rotation history key when a history file is requested
+fn load_history_key_if_needed(arguments: &RotateArgs) -> anyhow::Result<Option<cipher::Key<alkali::mem::FullAccess>>> {
+	arguments.history
+	         .as_ref()
+	         .map(|_| load_history_key(arguments))
+	         .transpose()
+	         .with_context(|| "Something went wrong while loading the rotation history key")
+}
+
+/// Encrypt an entry of the rotation history
+fn encrypt_history_entry(key: &cipher::Key<alkali::mem::FullAccess>, entry: RotationHistoryEntry) -> anyhow::Result<String> {
+	let plaintext: String = entry.into();
+	let mut ciphertext = vec![0u8; plaintext.len() + cipher::MAC_LENGTH];
+	let (_, nonce) = cipher::encrypt(plaintext.as_bytes(), key, None, &mut ciphertext)
+		.with_context(|| "Something went wrong while encrypting the rotation history entry")?;
+
+	Ok(EncryptedRotationHistoryEntry::new(
+		base64_url(&nonce).with_context(|| "Something went wrong while encoding the rotation history nonce")?,
+		base64_url(&ciphertext).with_context(|| "Something went wrong while encoding the rotation history entry")?,
+	).into())
+}
+
+/// Append an encrypted entry to the rotation history file
+fn append_history(path: &Path, encrypted_entry: &str) -> anyhow::Result<()> {
+	let history = path.to_str().ok_or(anyhow::anyhow!("Cannot convert rotation history file path to string"))?;
+
+	let mut stream_writer = StreamWriter::new(
+		history,
+		FileMode::builder().append().create().private().build()?,
+	).with_context(|| format!("Something went wrong while opening stream writer to {}", history))?;
+
+	stream_writer.write_all(format!("{}\n", encrypted_entry).as_bytes())?;
+
+	info!("Rotation history entry appended to {}", history);
+
+	Ok(())
+}
+
+/// Values to store in a file, along with its encrypted history entry if a history is kept
+struct PendingUpdate<'a> {
+	env: &'a Path,
+	values: Vec<(String, String)>,
+	encrypted_entry: Option<String>,
+}
+
+impl<'a> PendingUpdate<'a> {
+	/// Prepare the update of a file, encrypting its history entry before anything gets written
+	fn new(
+		env: &'a Path,
+		history_key: Option<&cipher::Key<alkali::mem::FullAccess>>,
+		(values, replaced_values): RotationValues,
+		operation: &'static str,
+	) -> anyhow::Result<Self> {
+		let encrypted_entry = history_key.map(|key| encrypt_history_entry(key, RotationHistoryEntry::new(operation, replaced_values)))
+		                                 .transpose()?;
+
+		Ok(Self { env, values, encrypted_entry })
+	}
+}
+
+/// Store the updated values and the history entries of all the files, unless running in dry run
+/// mode
+fn store(
+	global_arguments: &global_args::GlobalArgs,
+	keys_arguments: &KeysArgs,
+	arguments: &RotateArgs,
+	updates: &[PendingUpdate],
+) -> anyhow::Result<()> {
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	for update in updates {
+		env_file::protect_secret_file(update.env, keys_arguments.fix_permissions, keys_arguments.require_owned_directory)?;
+		env_file::update_values(update.env, &update.values)
+			.with_context(|| "Something went wrong while updating the environment file")?;
+		info!(".env file update completed for {}", update.env.display());
+
+		if let (Some(history), Some(encrypted_entry)) = (&arguments.history, &update.encrypted_entry) {
+			append_history(history, encrypted_entry)
+				.with_context(|| "Something went wrong while updating the rotation history")?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Rotate the keys moving the current values to the previous generation variables, every file gets
+/// the same new values while keeping its own previous generations
+pub fn rotate(global_arguments: &global_args::GlobalArgs, keys_arguments: &KeysArgs, arguments: &RotateArgs) -> anyhow::Result<()> {
+	trace!("{:?}", arguments);
+
+	let env_files = env_file::resolve_paths(&keys_arguments.env)?;
+	let history_key = load_history_key_if_needed(arguments)?;
+	let selected = select_variables(&keys_arguments.only, false, &HashMap::new())?;
+	let generated_values = generate_values(&selected)?;
+
+	// every file is prepared before any of them is updated, not to leave them half rotated
+	let mut updates = Vec::new();
+	for env in &env_files {
+		let existing_values = env_file::read_values(env)
+			.with_context(|| "Something went wrong while reading the current environment variables")?;
+
+		updates.push(PendingUpdate::new(
+			env,
+			history_key.as_ref(),
+			rotated_values(&existing_values, &selected, &generated_values, arguments),
+			"rotate",
+		)?);
+	}
+
+	store(global_arguments, keys_arguments, arguments, &updates)
+}
+
+/// Compute the values of a single file after a rotation, along with the replaced values
+fn rotated_values(
+	existing_values: &HashMap<String, String>,
+	selected: &[&'static str],
+	generated_values: &HashMap<&'static str, String>,
+	arguments: &RotateArgs,
+) -> RotationValues {
+	let mut values = Vec::new();
+	let mut replaced_values = BTreeMap::new();
+
+	for &name in selected {
+		let current = current_value(existing_values, name);
+
+		if env_file::is_placeholder(Some(&current)) {
+			warn!("{} has no value, nothing to move to the previous generation", name);
+		} else {
+			// shift the older generations first, the oldest one is dropped
+			for generation in (2..=arguments.generations).rev() {
+				values.push((
+					previous_variable_name(name, arguments, generation),
+					current_value(existing_values, &previous_variable_name(name, arguments, generation - 1)),
+				));
+			}
+
+			values.push((previous_variable_name(name, arguments, 1), current.clone()));
+			replaced_values.insert(name.to_owned(), current);
+		}
+
+		values.push((name.to_owned(), generated_values[name].clone()));
+		info!("Rotated {}", name);
+	}
+
+	(values, replaced_values)
+}
+
+/// Restore the previous generation of the keys
+pub fn rollback(global_arguments: &global_args::GlobalArgs, keys_arguments: &KeysArgs, arguments: &RotateArgs) -> anyhow::Result<()> {
+	trace!("{:?}", arguments);
+
+	let env_files = env_file::resolve_paths(&keys_arguments.env)?;
+	let history_key = load_history_key_if_needed(arguments)?;
+	let selected = select_variables(&keys_arguments.only, false, &HashMap::new())?;
+
+	// every file is checked before any of them is updated, not to leave them half rolled back
+	let mut updates = Vec::new();
+	for env in &env_files {
+		let existing_values = env_file::read_values(env)
+			.with_context(|| "Something went wrong while reading the current environment variables")?;
+
+		updates.push(PendingUpdate::new(
+			env,
+			history_key.as_ref(),
+			rolled_back_values(&existing_values, &selected, arguments)
+				.with_context(|| format!("Cannot roll back {}", env.display()))?,
+			"rollback",
+		)?);
+	}
+
+	store(global_arguments, keys_arguments, arguments, &updates)
+}
+
+/// Compute the values of a single file after a rollback, along with the replaced values
+fn rolled_back_values(
+	existing_values: &HashMap<String, String>,
+	selected: &[&'static str],
+	arguments: &RotateArgs,
+) -> anyhow::Result<RotationValues> {
+	let mut values = Vec::new();
+	let mut replaced_values = BTreeMap::new();
+
+	for &name in selected {
+		let previous = current_value(existing_values, &previous_variable_name(name, arguments, 1));
+
+		if env_file::is_placeholder(Some(&previous)) {
+			anyhow::bail!(CompanionError::Validation(format!("No previous generation of {} to roll back to", name)));
+		}
+
+		replaced_values.insert(name.to_owned(), current_value(existing_values, name));
+		values.push((name.to_owned(), previous));
+
+		// shift the newer generations back, the oldest one is emptied
+		for generation in 1..arguments.generations {
+			values.push((
+				previous_variable_name(name, arguments, generation),
+				current_value(existing_values, &previous_variable_name(name, arguments, generation + 1)),
+			));
+		}
+		values.push((previous_variable_name(name, arguments, arguments.generations), String::new()));
+
+		info!("Rolled back {}", name);
+	}
+
+	Ok((values, replaced_values))
+}
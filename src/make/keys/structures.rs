@@ -1,2 +1,7 @@
 pub mod environment_record;
 pub mod environment_variables;
+pub mod json_web_key;
+pub mod key_inspection;
+pub mod key_verification;
+pub mod kubernetes_secret;
+pub mod rotation_history_entry;
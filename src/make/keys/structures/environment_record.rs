@@ -9,6 +9,8 @@ pub struct EnvironmentRecord<'a> {
 	value: &'a str,
 	/// Whether the environment variable was updated or not
 	updated: bool,
+	/// Whether the environment variable must be left untouched
+	skipped: bool,
 }
 json_serialize_to_string!(EnvironmentRecord<'_>);
 
@@ -19,6 +21,7 @@ impl<'a> EnvironmentRecord<'a> {
 			env_name,
 			value: initial_value,
 			updated: false,
+			skipped: false,
 		}
 	}
 
@@ -27,6 +30,11 @@ impl<'a> EnvironmentRecord<'a> {
 		self.updated = true;
 	}
 
+	/// Leave the environment variable untouched
+	pub fn set_as_skipped(&mut self) {
+		self.skipped = true;
+	}
+
 	/// Get the name of the environment variable
 	pub fn name(&self) -> &str {
 		self.env_name
@@ -41,4 +49,9 @@ impl<'a> EnvironmentRecord<'a> {
 	pub fn updated(&self) -> bool {
 		self.updated
 	}
+
+	/// Whether the environment variable must be left untouched
+	pub fn skipped(&self) -> bool {
+		self.skipped
+	}
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use alkali::hash::sha2::sha256;
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::helpers::base64_url_no_padding;
+use crate::json_serialize_to_string;
+
+/// Octet key pair curves supported by the JWK representation (RFC 8037)
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum OctetKeyPairCurve {
+	/// Key exchange curve, used by the asymmetric encryption keypair
+	X25519,
+}
+
+impl OctetKeyPairCurve {
+	/// Get the JOSE algorithm identifier for the curve
+	fn algorithm(&self) -> &'static str {
+		match self {
+			OctetKeyPairCurve::X25519 => "ECDH-ES",
+		}
+	}
+
+	/// Get the JWK intended use for the curve
+	fn intended_use(&self) -> &'static str {
+		match self {
+			OctetKeyPairCurve::X25519 => "enc",
+		}
+	}
+}
+
+/// Single JSON Web Key, as defined in RFC 7517
+#[derive(Serialize, Clone, Debug)]
+pub struct JsonWebKey {
+	/// Key type, always "OKP" for the supported curves
+	kty: &'static str,
+	/// Curve of the key
+	crv: OctetKeyPairCurve,
+	/// Base64 url encoded public key
+	x: String,
+	/// Base64 url encoded private key, only present if requested
+	#[serde(skip_serializing_if = "Option::is_none")]
+	d: Option<String>,
+	/// Intended use of the key
+	#[serde(rename = "use")]
+	intended_use: &'static str,
+	/// Algorithm the key is meant to be used with
+	alg: &'static str,
+	/// Key identifier, derived from the key thumbprint
+	kid: String,
+}
+json_serialize_to_string!(JsonWebKey);
+
+impl JsonWebKey {
+	/// Create a new JWK from the raw public and (optionally) private keys
+	pub fn new(crv: OctetKeyPairCurve, public_key: &[u8], private_key: Option<&[u8]>) -> anyhow::Result<Self> {
+		let x = base64_url_no_padding(public_key)
+			.with_context(|| "Something went wrong while encoding the JWK public key")?;
+		let d = private_key.map(base64_url_no_padding)
+		                   .transpose()
+		                   .with_context(|| "Something went wrong while encoding the JWK private key")?;
+		let kid = Self::thumbprint(crv, &x)?;
+
+		Ok(Self {
+			kty: "OKP",
+			crv,
+			x,
+			d,
+			intended_use: crv.intended_use(),
+			alg: crv.algorithm(),
+			kid,
+		})
+	}
+
+	/// Compute the RFC 7638 SHA-256 thumbprint of an octet key pair public key
+	pub fn thumbprint(crv: OctetKeyPairCurve, x: &str) -> anyhow::Result<String> {
+		// required members only, in lexicographic order and without whitespaces
+		let canonical = format!("{{\"crv\":\"{:?}\",\"kty\":\"OKP\",\"x\":\"{}\"}}", crv, x);
+
+		let digest = sha256::hash(canonical.as_bytes())
+			.with_context(|| "Something went wrong while hashing the JWK thumbprint")?;
+
+		base64_url_no_padding(&digest.0).with_context(|| "Something went wrong while encoding the JWK thumbprint")
+	}
+
+	/// Get the key identifier
+	pub fn kid(&self) -> &str {
+		&self.kid
+	}
+}
+
+/// Set of JSON Web Keys, as defined in RFC 7517 section 5
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct JsonWebKeySet {
+	keys: Vec<JsonWebKey>,
+}
+json_serialize_to_string!(JsonWebKeySet);
+
+impl JsonWebKeySet {
+	/// Add a key to the set
+	pub fn push(&mut self, key: JsonWebKey) {
+		self.keys.push(key);
+	}
+}
+
+#[test]
+fn can_compute_rfc_8037_thumbprint() {
+	// RFC 8037 only publishes an Ed25519 thumbprint (appendix A.3), this vector uses the X25519
+	// public key of Alice published in RFC 7748 section 6.1
+	// (8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a), the expected thumbprint
+	// is the SHA-256 of the RFC 7638 canonical JSON computed with OpenSSL:
+	// printf '%s' '{"crv":"X25519","kty":"OKP","x":"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo"}' \
+	//     | openssl dgst -sha256 -binary | basenc --base64url | tr -d '='
+	let thumbprint = JsonWebKey::thumbprint(
+		OctetKeyPairCurve::X25519,
+		"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo",
+	).unwrap();
+
+	assert_eq!(thumbprint, "u809Vppx5ixWMOohxWr2aM3m5bD0LQ67g_GPmubQus4");
+}
+
+#[test]
+fn can_omit_private_key() {
+	let key = JsonWebKey::new(OctetKeyPairCurve::X25519, &[1u8; 32], None).unwrap();
+	let json: String = key.into();
+
+	assert!(json.contains("\"kty\":\"OKP\""));
+	assert!(json.contains("\"crv\":\"X25519\""));
+	assert!(json.contains("\"use\":\"enc\""));
+	assert!(!json.contains("\"d\""));
+}
@@ -0,0 +1,115 @@
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Kind of a managed key
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyKind {
+	/// Symmetric secret, e.g. the NextAuth secret
+	Symmetric,
+	/// Public half of the X25519 asymmetric encryption keypair
+	X25519Public,
+	/// Private half of the X25519 asymmetric encryption keypair
+	X25519Private,
+}
+
+impl KeyKind {
+	/// Get the human readable name of the kind
+	pub fn label(&self) -> &'static str {
+		match self {
+			KeyKind::Symmetric => "symmetric",
+			KeyKind::X25519Public => "x25519 public",
+			KeyKind::X25519Private => "x25519 private",
+		}
+	}
+}
+
+/// Inspection result of a single key, never holding the key itself
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyInspection {
+	/// Environment file the key was read from
+	env_file: String,
+	/// Raw environment variable name
+	env_name: String,
+	/// Kind of the key
+	kind: KeyKind,
+	/// Decoded length of the key in bytes, if it could be decoded
+	length: Option<usize>,
+	/// Short BLAKE2b fingerprint of the stored value, matching the one of the masked values, if the
+	/// key could be decoded
+	fingerprint: Option<String>,
+	/// Whether the keypair halves match, for keypairs whose both halves could be decoded
+	keypair_match: Option<bool>,
+	/// Human readable explanation of the missing information
+	details: String,
+}
+json_serialize_to_string!(KeyInspection);
+
+impl KeyInspection {
+	/// Create a new inspection result of a key that could not be decoded
+	pub fn new(env_file: impl Into<String>, env_name: impl Into<String>, kind: KeyKind, details: impl Into<String>) -> Self {
+		Self {
+			env_file: env_file.into(),
+			env_name: env_name.into(),
+			kind,
+			length: None,
+			fingerprint: None,
+			keypair_match: None,
+			details: details.into(),
+		}
+	}
+
+	/// Record the decoded key length and fingerprint
+	pub fn set_decoded(&mut self, length: usize, fingerprint: String) {
+		self.length = Some(length);
+		self.fingerprint = Some(fingerprint);
+	}
+
+	/// Record whether the keypair halves match
+	pub fn set_keypair_match(&mut self, keypair_match: bool) {
+		self.keypair_match = Some(keypair_match);
+	}
+
+	/// Get the environment file the key was read from
+	pub fn env_file(&self) -> &str {
+		&self.env_file
+	}
+
+	/// Get the name of the environment variable
+	pub fn name(&self) -> &str {
+		&self.env_name
+	}
+
+	/// Get the kind of the key
+	pub fn kind(&self) -> KeyKind {
+		self.kind
+	}
+
+	/// Get the decoded length of the key
+	pub fn length(&self) -> Option<usize> {
+		self.length
+	}
+
+	/// Get the fingerprint of the key
+	pub fn fingerprint(&self) -> Option<&str> {
+		self.fingerprint.as_deref()
+	}
+
+	/// Get whether the keypair halves match
+	pub fn keypair_match(&self) -> Option<bool> {
+		self.keypair_match
+	}
+
+	/// Get the explanation of the missing information
+	pub fn details(&self) -> &str {
+		&self.details
+	}
+}
+
+/// Inspection results of all the keys of one or more environment files
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct KeyInspectionReport {
+	pub results: Vec<KeyInspection>,
+}
+json_serialize_to_string!(KeyInspectionReport);
@@ -0,0 +1,82 @@
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Outcome of the verification of a single key
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyVerificationStatus {
+	/// The key is correctly encoded, sized and paired
+	Valid,
+	/// The environment variable is not defined
+	Missing,
+	/// The environment variable is empty or holds a placeholder
+	Placeholder,
+	/// The value cannot be decoded or has the wrong length
+	Malformed,
+	/// The public key does not belong to the private key
+	Mismatched,
+}
+
+/// Verification result of a single environment variable
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyVerification {
+	/// Raw environment variable name
+	env_name: &'static str,
+	/// Verification outcome
+	status: KeyVerificationStatus,
+	/// Human readable explanation of the outcome
+	details: String,
+}
+json_serialize_to_string!(KeyVerification);
+
+impl KeyVerification {
+	/// Create a new verification result
+	pub fn new(env_name: &'static str, status: KeyVerificationStatus, details: impl Into<String>) -> Self {
+		Self {
+			env_name,
+			status,
+			details: details.into(),
+		}
+	}
+
+	/// Get the name of the environment variable
+	pub fn name(&self) -> &str {
+		self.env_name
+	}
+
+	/// Get the verification outcome
+	pub fn status(&self) -> KeyVerificationStatus {
+		self.status
+	}
+
+	/// Update the verification outcome
+	pub fn set_status(&mut self, status: KeyVerificationStatus, details: impl Into<String>) {
+		self.status = status;
+		self.details = details.into();
+	}
+
+	/// Get the explanation of the outcome
+	pub fn details(&self) -> &str {
+		&self.details
+	}
+
+	/// Whether the key is valid
+	pub fn is_valid(&self) -> bool {
+		self.status == KeyVerificationStatus::Valid
+	}
+}
+
+/// Verification results of all the managed environment variables
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct KeyVerificationReport {
+	pub results: Vec<KeyVerification>,
+}
+json_serialize_to_string!(KeyVerificationReport);
+
+impl KeyVerificationReport {
+	/// Whether all the keys are valid
+	pub fn is_valid(&self) -> bool {
+		self.results.iter().all(KeyVerification::is_valid)
+	}
+}
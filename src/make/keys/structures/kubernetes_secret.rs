@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Metadata of a Kubernetes object
+#[derive(Serialize, Clone, Debug)]
+pub struct KubernetesMetadata {
+	/// Name of the object in its namespace
+	name: String,
+}
+
+/// Kubernetes `Secret` manifest, holding standard base64 encoded values
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KubernetesSecret {
+	/// API version of the object, always "v1"
+	api_version: &'static str,
+	/// Kind of the object, always "Secret"
+	kind: &'static str,
+	metadata: KubernetesMetadata,
+	/// Secret type, always "Opaque" for arbitrary user data
+	#[serde(rename = "type")]
+	secret_type: &'static str,
+	/// Base64 encoded values, by environment variable name
+	data: BTreeMap<String, String>,
+}
+json_serialize_to_string!(KubernetesSecret);
+
+impl KubernetesSecret {
+	/// Create an opaque secret from already base64 encoded values
+	pub fn new(name: impl Into<String>, data: BTreeMap<String, String>) -> Self {
+		Self {
+			api_version: "v1",
+			kind: "Secret",
+			metadata: KubernetesMetadata { name: name.into() },
+			secret_type: "Opaque",
+			data,
+		}
+	}
+}
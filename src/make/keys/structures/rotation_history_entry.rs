@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Single rotation (or rollback) event, stored encrypted in the rotation history
+#[derive(Serialize, Clone, Debug)]
+pub struct RotationHistoryEntry {
+	/// Action that generated the entry, either "rotate" or "rollback"
+	action: &'static str,
+	/// Unix timestamp (in seconds) of the action
+	timestamp: u64,
+	/// Values replaced by the action, indexed by environment variable name
+	variables: BTreeMap<String, String>,
+}
+json_serialize_to_string!(RotationHistoryEntry);
+
+impl RotationHistoryEntry {
+	/// Create a new entry with the current timestamp
+	pub fn new(action: &'static str, variables: BTreeMap<String, String>) -> Self {
+		let timestamp = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map(|duration| duration.as_secs())
+			.unwrap_or_default();
+
+		Self {
+			action,
+			timestamp,
+			variables,
+		}
+	}
+}
+
+/// Encrypted rotation history entry, stored as a single JSON line in the history file
+#[derive(Serialize, Clone, Debug)]
+pub struct EncryptedRotationHistoryEntry {
+	/// Base64 url encoded nonce used for the encryption
+	nonce: String,
+	/// Base64 url encoded ciphertext of the serialized entry
+	ciphertext: String,
+}
+json_serialize_to_string!(EncryptedRotationHistoryEntry);
+
+impl EncryptedRotationHistoryEntry {
+	/// Create a new encrypted entry from its already encoded parts
+	pub fn new(nonce: String, ciphertext: String) -> Self {
+		Self {
+			nonce,
+			ciphertext,
+		}
+	}
+}
@@ -1,25 +1,88 @@
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{Attribute, Cell, Row, Table};
+use crate::helpers::mask;
+use crate::make::keys::constants::SECRET_ENV_VARIABLES;
 use crate::make::keys::structures::environment_record::EnvironmentRecord;
+use crate::make::keys::structures::key_inspection::KeyInspectionReport;
+use crate::make::keys::structures::key_verification::KeyVerificationReport;
 use crate::make::keys::structures::environment_variables::{ENVIRONMENT_VARIABLES_KEYS, EnvironmentVariables};
 
-/// Pack the environment variables into a vector of rows to be used by the table
-fn pack_table_rows(env_variables: &EnvironmentVariables) -> Vec<Row> {
+/// Pack the environment variables into a vector of rows to be used by the table, masking the
+/// secrets unless revealed
+fn pack_table_rows(env_variables: &EnvironmentVariables, reveal: bool) -> Vec<Row> {
 	ENVIRONMENT_VARIABLES_KEYS.iter()
 	                          .map(|key| &env_variables[key] as &EnvironmentRecord) // get the value of the key (the struct key hardcoded in the array)
-	                          .map(|ev| Row::from(vec![ev.name(), ev.value()]))
+	                          .filter(|ev| !ev.skipped())
+	                          .map(|ev| {
+		                          let value = if reveal || !SECRET_ENV_VARIABLES.contains(&ev.name()) { ev.value().to_owned() } else { mask(ev.value()) };
+		                          Row::from(vec![ev.name().to_owned(), value])
+	                          })
 	                          .collect()
 }
 
 /// Display the environment variables table
-pub fn display_environment_variables_table(env_variables: &EnvironmentVariables) {
+pub fn display_environment_variables_table(env_variables: &EnvironmentVariables, reveal: bool) {
 	let mut table = Table::new();
 	table.load_preset(UTF8_FULL)
 	     .set_header(vec![
 		     Cell::new("Environment variable name").add_attribute(Attribute::Bold),
 		     Cell::new("Value").add_attribute(Attribute::Bold),
 	     ])
-	     .add_rows(pack_table_rows(env_variables));
+	     .add_rows(pack_table_rows(env_variables, reveal));
 
 	println!("{table}");
-}
\ No newline at end of file
+}
+
+/// Display the key verification table
+pub fn display_key_verification_table(report: &KeyVerificationReport) {
+	let mut table = Table::new();
+	table.load_preset(UTF8_FULL)
+	     .set_header(vec![
+		     Cell::new("Environment variable name").add_attribute(Attribute::Bold),
+		     Cell::new("Status").add_attribute(Attribute::Bold),
+		     Cell::new("Details").add_attribute(Attribute::Bold),
+	     ])
+	     .add_rows(
+		     report.results
+		           .iter()
+		           .map(|result| Row::from(vec![
+			           result.name().to_owned(),
+			           format!("{:?}", result.status()),
+			           result.details().to_owned(),
+		           ]))
+	     );
+
+	println!("{table}");
+}
+
+/// Display the key inspection table
+pub fn display_key_inspection_table(report: &KeyInspectionReport) {
+	let mut table = Table::new();
+	table.load_preset(UTF8_FULL)
+	     .set_header(vec![
+		     Cell::new("File").add_attribute(Attribute::Bold),
+		     Cell::new("Environment variable name").add_attribute(Attribute::Bold),
+		     Cell::new("Kind").add_attribute(Attribute::Bold),
+		     Cell::new("Length").add_attribute(Attribute::Bold),
+		     Cell::new("Fingerprint").add_attribute(Attribute::Bold),
+		     Cell::new("Keypair").add_attribute(Attribute::Bold),
+	     ])
+	     .add_rows(
+		     report.results
+		           .iter()
+		           .map(|result| Row::from(vec![
+			           result.env_file().to_owned(),
+			           result.name().to_owned(),
+			           result.kind().label().to_owned(),
+			           result.length().map(|length| format!("{} bytes", length)).unwrap_or_else(|| result.details().to_owned()),
+			           result.fingerprint().unwrap_or("-").to_owned(),
+			           match result.keypair_match() {
+				           Some(true) => "matching".to_owned(),
+				           Some(false) => "mismatched".to_owned(),
+				           None => "-".to_owned(),
+			           },
+		           ]))
+	     );
+
+	println!("{table}");
+}
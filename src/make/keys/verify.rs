@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use alkali::{asymmetric::kx, symmetric::cipher};
+use anyhow::Context;
+use log::{error, info};
+
+use crate::env::env_file;
+use crate::error::CompanionError;
+use crate::events;
+use crate::global_args;
+use crate::helpers::base64_url_decode;
+use crate::make::keys::structures::key_verification::{KeyVerification, KeyVerificationReport, KeyVerificationStatus};
+use crate::make::keys::{constants, table, KeysArgs};
+
+/// Expected decoded length (in bytes) of each managed environment variable
+const EXPECTED_LENGTHS: [(&str, usize); 3] = [
+	(constants::ENV_VARIABLE__NEXTAUTH_SECRET, cipher::KEY_LENGTH),
+	(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY, kx::PUBLIC_KEY_LENGTH),
+	(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY, kx::PRIVATE_KEY_LENGTH),
+];
+
+/// Decode and check the length of a single key
+fn verify_key(name: &'static str, expected_length: usize, value: Option<&String>) -> (KeyVerification, Option<Vec<u8>>) {
+	let value = match value {
+		None => return (KeyVerification::new(name, KeyVerificationStatus::Missing, "Variable is not defined"), None),
+		Some(value) => value,
+	};
+
+	if env_file::is_placeholder(Some(value)) {
+		return (KeyVerification::new(name, KeyVerificationStatus::Placeholder, "Variable is empty or holds a placeholder"), None);
+	}
+
+	match base64_url_decode(value) {
+		Err(_) => (KeyVerification::new(name, KeyVerificationStatus::Malformed, "Value is not base64 url encoded"), None),
+		Ok(key) if key.len() != expected_length => (
+			KeyVerification::new(
+				name,
+				KeyVerificationStatus::Malformed,
+				format!("Decoded key is {} bytes long, expected {} bytes", key.len(), expected_length),
+			),
+			None,
+		),
+		Ok(key) => (KeyVerification::new(name, KeyVerificationStatus::Valid, format!("{} bytes key", key.len())), Some(key)),
+	}
+}
+
+/// Check that the public key is derived from the private key
+pub(crate) fn is_matching_keypair(public_key: &[u8], private_key: &[u8]) -> anyhow::Result<bool> {
+	let private_key = kx::PrivateKey::try_from(private_key)
+		.with_context(|| "Something went wrong while loading the private key")?;
+	let keypair = kx::Keypair::from_private_key(&private_key)
+		.with_context(|| "Something went wrong while deriving the public key")?;
+
+	Ok(keypair.public_key.as_slice() == public_key)
+}
+
+/// Verify all the managed keys stored in the given set of values
+pub fn verify_values(values: &HashMap<String, String>) -> anyhow::Result<KeyVerificationReport> {
+	let mut report = KeyVerificationReport::default();
+	let mut decoded_keys = HashMap::new();
+
+	for (name, expected_length) in EXPECTED_LENGTHS {
+		let (verification, key) = verify_key(name, expected_length, values.get(name));
+		report.results.push(verification);
+
+		if let Some(key) = key {
+			decoded_keys.insert(name, key);
+		}
+	}
+
+	if let (Some(public_key), Some(private_key)) = (
+		decoded_keys.get(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY),
+		decoded_keys.get(constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY),
+	) {
+		if !is_matching_keypair(public_key, private_key)? {
+			report.results
+			      .iter_mut()
+			      .filter(|result| result.name() == constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY)
+			      .for_each(|result| result.set_status(
+				      KeyVerificationStatus::Mismatched,
+				      "Public key is not derived from the private key",
+			      ));
+		}
+	}
+
+	Ok(report)
+}
+
+/// Verify the keys stored in a single environment file, returning whether all of them are valid
+fn verify_file(global_arguments: &global_args::GlobalArgs, env: &Path) -> anyhow::Result<bool> {
+	info!("Verifying encryption keys in {}", env.display());
+
+	let values = env_file::read_values(env)
+		.with_context(|| "Something went wrong while reading the current environment variables")?;
+	let report = verify_values(&values)?;
+	let is_valid = report.is_valid();
+
+	if !global_arguments.json {
+		table::display_key_verification_table(&report);
+	} else {
+		events::insert_json("verification", &report)?;
+	}
+
+	if !is_valid {
+		error!("Encryption keys verification failed in {}", env.display());
+	} else {
+		info!("Encryption keys verified successfully in {}", env.display());
+	}
+
+	Ok(is_valid)
+}
+
+/// Verify the keys stored in the environment files, failing if any of them is invalid
+pub fn verify(global_arguments: &global_args::GlobalArgs, arguments: &KeysArgs) -> anyhow::Result<()> {
+	let mut invalid_files = Vec::new();
+
+	for env in env_file::resolve_paths(&arguments.env)? {
+		if !verify_file(global_arguments, &env)? {
+			invalid_files.push(env.display().to_string());
+		}
+	}
+
+	if !invalid_files.is_empty() {
+		anyhow::bail!(CompanionError::Validation(format!("One or more encryption keys in {} are invalid", invalid_files.join(", "))));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_detect_mismatched_keypair() {
+	use crate::helpers::base64_url;
+
+	let keypair = kx::Keypair::generate().unwrap();
+	let other_keypair = kx::Keypair::generate().unwrap();
+
+	let mut values = HashMap::new();
+	values.insert(constants::ENV_VARIABLE__NEXTAUTH_SECRET.to_owned(), base64_url(&[0u8; 16]).unwrap());
+	values.insert(
+		constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY.to_owned(),
+		base64_url(other_keypair.public_key.as_slice()).unwrap(),
+	);
+	values.insert(
+		constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY.to_owned(),
+		base64_url(keypair.private_key.as_slice()).unwrap(),
+	);
+
+	let report = verify_values(&values).unwrap();
+	let statuses: Vec<KeyVerificationStatus> = report.results.iter().map(KeyVerification::status).collect();
+
+	assert!(!report.is_valid());
+	assert_eq!(statuses, vec![
+		KeyVerificationStatus::Malformed,
+		KeyVerificationStatus::Mismatched,
+		KeyVerificationStatus::Valid,
+	]);
+}
@@ -2,7 +2,7 @@ use clap::Args;
 use log::{trace, info, warn};
 use crate::global_args;
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Default)]
 pub struct SignaturesArgs {
 	/// Watch procedure index files for new procedures and update signatures as needed
 	#[arg(long, short)]
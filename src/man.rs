@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Args;
+use log::{info, trace, warn};
+
+use crate::env::env_file;
+use crate::events;
+use crate::global_args;
+
+#[derive(Args, Debug)]
+pub struct ManArgs {
+	/// Directory the man pages are written to
+	#[arg(long, short, value_name = "DIR", default_value = "man")]
+	pub output: PathBuf,
+
+	/// Section of the manual the pages belong to
+	#[arg(long, default_value = "1")]
+	pub section: String,
+}
+
+/// Render the man page of a command and of all its visible subcommands, the pages of the
+/// subcommands are named after their path, e.g. `saas-template-companion-make-keys`
+pub fn render_pages(command: &clap::Command, section: &str) -> anyhow::Result<Vec<(String, String)>> {
+	let name = command.get_display_name().unwrap_or(command.get_name()).to_owned();
+	let mut buffer = Vec::new();
+	clap_mangen::Man::new(command.clone())
+		.section(section)
+		.render(&mut buffer)
+		.with_context(|| format!("Something went wrong while rendering the man page of {}", name))?;
+
+	let mut pages = vec![(
+		name.clone(),
+		String::from_utf8(buffer).with_context(|| format!("The man page of {} is not valid UTF-8", name))?,
+	)];
+
+	for subcommand in command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()) {
+		let subcommand = subcommand.clone().display_name(format!("{}-{}", name, subcommand.get_name()));
+		pages.extend(render_pages(&subcommand, section)?);
+	}
+
+	Ok(pages)
+}
+
+/// Write the man pages of the given command line definition
+pub fn handle(
+	global_arguments: &global_args::GlobalArgs,
+	arguments: &ManArgs,
+	command: &mut clap::Command,
+) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	trace!("{:?}", arguments);
+
+	// propagate the global arguments to every subcommand page
+	command.build();
+	let pages = render_pages(command, &arguments.section)?;
+
+	if global_arguments.json {
+		events::insert_json("pages", &pages.iter().map(|(name, _)| name).collect::<Vec<_>>())?;
+	}
+
+	if global_arguments.dry_run {
+		warn!("Dry run, skipping file update");
+		return Ok(());
+	}
+
+	std::fs::create_dir_all(&arguments.output)
+		.with_context(|| format!("Something went wrong while creating {}", arguments.output.display()))?;
+
+	for (name, content) in &pages {
+		let path = arguments.output.join(format!("{}.{}", name, arguments.section));
+		env_file::write_public_content(&path, content)
+			.with_context(|| format!("Something went wrong while writing {}", path.display()))?;
+	}
+	info!("{} man pages written to {}", pages.len(), arguments.output.display());
+
+	Ok(())
+}
+
+#[test]
+fn can_render_subcommand_pages() {
+	let command = clap::Command::new("companion")
+		.subcommand(clap::Command::new("make").subcommand(clap::Command::new("keys").about("Generate the keys")))
+		.subcommand(clap::Command::new("internal").hide(true));
+
+	let pages = render_pages(&command, "1").unwrap();
+	let names: Vec<&str> = pages.iter().map(|(name, _)| name.as_str()).collect();
+	assert_eq!(names, ["companion", "companion-make", "companion-make-keys"]);
+	assert!(pages[2].1.contains(".TH companion-make-keys 1"));
+	assert!(pages[2].1.contains("Generate the keys"));
+}
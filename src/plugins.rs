@@ -0,0 +1,146 @@
+//! External subcommands, git and cargo style.
+//!
+//! Running `saas-template-companion stripe charge --amount 10` executes the
+//! `saas-template-companion-stripe` executable found on `PATH` with `charge --amount 10` as
+//! arguments. The plugin receives:
+//!
+//! - the global arguments as `SAAS_TEMPLATE_COMPANION_*` environment variables, see
+//!   [`environment_variables`]
+//! - a [`PluginContext`] JSON object, followed by a new line, on its standard input
+//!
+//! Its standard output and error are the ones of the companion, and the companion exits with the
+//! exit code of the plugin.
+
+use std::ffi::OsString;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use anyhow::Context;
+use log::{debug, trace};
+
+use crate::error::CompanionError;
+use crate::global_args;
+use crate::structures::plugin_context::{PluginContext, PluginGlobalArgs};
+
+/// Prefix of the environment variables passed to the plugins
+pub const ENV_PREFIX: &str = "SAAS_TEMPLATE_COMPANION_";
+
+/// Name of the executable implementing the given external subcommand
+pub fn executable_name(command: &str) -> String {
+	format!("{}-{}{}", clap::crate_name!(), command, std::env::consts::EXE_SUFFIX)
+}
+
+/// Whether the path is a file the current user can execute
+fn is_executable(path: &Path) -> bool {
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		path.metadata().is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+	}
+	#[cfg(not(unix))]
+	{
+		path.is_file()
+	}
+}
+
+/// Find the executable implementing the given external subcommand on `PATH`
+pub fn find_plugin(command: &str) -> Option<PathBuf> {
+	let name = executable_name(command);
+	let paths = std::env::var_os("PATH")?;
+
+	std::env::split_paths(&paths)
+		.map(|directory| directory.join(&name))
+		.find(|path| is_executable(path))
+}
+
+/// Environment variables holding the global arguments of the companion
+pub fn environment_variables(global_args: &PluginGlobalArgs) -> Vec<(String, String)> {
+	let mut variables = vec![
+		(format!("{}DRY_RUN", ENV_PREFIX), global_args.dry_run.to_string()),
+		(format!("{}JSON", ENV_PREFIX), global_args.json.to_string()),
+		(format!("{}LOG_LEVEL", ENV_PREFIX), global_args.log_level.clone()),
+	];
+	if let Some(log_file) = &global_args.log_file {
+		variables.push((format!("{}LOG_FILE", ENV_PREFIX), log_file.clone()));
+	}
+
+	variables
+}
+
+/// Run the external subcommand, the first argument is its name
+pub fn handle(global_arguments: &global_args::GlobalArgs, arguments: &[OsString]) -> anyhow::Result<()> {
+	trace!("{:?}", global_arguments);
+	trace!("{:?}", arguments);
+
+	let (command, args) = arguments.split_first().with_context(|| "Missing external subcommand name")?;
+	let command = command.to_str()
+	                     .ok_or_else(|| CompanionError::Config(format!("Invalid command name {:?}", command)))?;
+	let plugin = find_plugin(command).ok_or_else(|| CompanionError::Config(format!(
+		"Unknown command {}, no {} executable found on PATH",
+		command,
+		executable_name(command),
+	)))?;
+
+	let context = PluginContext::new(
+		command,
+		args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect(),
+		PluginGlobalArgs {
+			dry_run: global_arguments.dry_run,
+			json: global_arguments.json,
+			log_level: global_arguments.verbose.log_level_filter().as_str().to_lowercase(),
+			log_file: global_arguments.log_file.as_ref().map(|path| path.display().to_string()),
+		},
+		std::env::current_dir()
+			.with_context(|| "Something went wrong while reading the working directory")?
+			.display()
+			.to_string(),
+	);
+
+	debug!("Running {} {:?}", plugin.display(), args);
+	let mut child = std::process::Command::new(&plugin)
+		.args(args)
+		.envs(environment_variables(context.global_args()))
+		.stdin(Stdio::piped())
+		.spawn()
+		.with_context(|| format!("Something went wrong while running {}", plugin.display()))?;
+
+	let mut stdin = child.stdin.take().with_context(|| format!("Cannot write to the standard input of {}", plugin.display()))?;
+	let context: String = context.into();
+	match stdin.write_all(format!("{}\n", context).as_bytes()) {
+		// the plugin does not have to read its context
+		Err(error) if error.kind() == ErrorKind::BrokenPipe => {}
+		result => result.with_context(|| format!("Cannot write the context to the standard input of {}", plugin.display()))?,
+	}
+	drop(stdin);
+
+	let status = child.wait()
+	                  .with_context(|| format!("Something went wrong while waiting for {}", plugin.display()))?;
+	if !status.success() {
+		anyhow::bail!(CompanionError::Plugin(
+			format!("{} exited with {}", executable_name(command), status),
+			status.code().unwrap_or(exitcode::SOFTWARE),
+		));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_pass_global_args_as_environment_variables() {
+	let global_args = PluginGlobalArgs {
+		dry_run: true,
+		json: false,
+		log_level: "info".to_owned(),
+		log_file: None,
+	};
+
+	assert_eq!(
+		environment_variables(&global_args),
+		[
+			("SAAS_TEMPLATE_COMPANION_DRY_RUN".to_owned(), "true".to_owned()),
+			("SAAS_TEMPLATE_COMPANION_JSON".to_owned(), "false".to_owned()),
+			("SAAS_TEMPLATE_COMPANION_LOG_LEVEL".to_owned(), "info".to_owned()),
+		],
+	);
+}
@@ -0,0 +1,116 @@
+use std::io;
+use std::sync::Mutex;
+
+use log::Record;
+use log4rs::encode::{self, Encode, Style};
+
+use crate::helpers::mask;
+
+/// Values shorter than this are not registered, as scrubbing them would mangle unrelated text
+const MINIMUM_SECRET_LENGTH: usize = 8;
+
+/// Secret values to scrub from every log line
+static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Register a secret value, any later log line containing it gets the value masked
+pub fn register_secret(value: &str) {
+	if value.chars().count() < MINIMUM_SECRET_LENGTH {
+		return;
+	}
+
+	if let Ok(mut secrets) = SECRETS.lock() {
+		if !secrets.iter().any(|secret| secret == value) {
+			secrets.push(value.to_owned());
+			// longest first, so that a secret containing another one is masked as a whole
+			secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+		}
+	}
+}
+
+/// Mask all the registered secret values found in the text
+pub fn redact(text: &str) -> String {
+	let Ok(secrets) = SECRETS.lock() else {
+		return text.to_owned();
+	};
+
+	secrets.iter()
+	       .fold(text.to_owned(), |text, secret| {
+		       if text.contains(secret.as_str()) { text.replace(secret.as_str(), &mask(secret)) } else { text }
+	       })
+}
+
+/// Piece of an encoded log line, either text or a style change
+#[derive(Debug)]
+enum Chunk {
+	Text(Vec<u8>),
+	Style(Style),
+}
+
+/// Writer buffering an encoded log line, keeping the style changes in place
+#[derive(Debug, Default)]
+struct BufferedWriter {
+	chunks: Vec<Chunk>,
+}
+
+impl io::Write for BufferedWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self.chunks.last_mut() {
+			Some(Chunk::Text(text)) => text.extend_from_slice(buf),
+			_ => self.chunks.push(Chunk::Text(buf.to_vec())),
+		}
+
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl encode::Write for BufferedWriter {
+	fn set_style(&mut self, style: &Style) -> io::Result<()> {
+		self.chunks.push(Chunk::Style(style.clone()));
+
+		Ok(())
+	}
+}
+
+/// Encoder wrapping another one, scrubbing the registered secret values from its output
+#[derive(Debug)]
+pub struct RedactingEncoder {
+	inner: Box<dyn Encode>,
+}
+
+impl RedactingEncoder {
+	/// Wrap an encoder
+	pub fn new(inner: Box<dyn Encode>) -> Self {
+		Self { inner }
+	}
+}
+
+impl Encode for RedactingEncoder {
+	fn encode(&self, w: &mut dyn encode::Write, record: &Record) -> anyhow::Result<()> {
+		let mut buffer = BufferedWriter::default();
+		self.inner.encode(&mut buffer, record)?;
+
+		for chunk in buffer.chunks {
+			match chunk {
+				Chunk::Text(text) => w.write_all(redact(&String::from_utf8_lossy(&text)).as_bytes())?,
+				Chunk::Style(style) => w.set_style(&style)?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[test]
+fn can_redact_registered_secrets() {
+	register_secret("redaction_test_secret_value");
+	register_secret("short");
+
+	let redacted = redact("value is redaction_test_secret_value, short stays");
+	assert!(!redacted.contains("redaction_test_secret_value"));
+	assert!(redacted.contains(&mask("redaction_test_secret_value")));
+	assert!(redacted.contains("short stays"));
+}
@@ -1,4 +1,8 @@
 pub mod stream_reader;
+pub mod stream_writer;
 pub mod file_mode;
 pub mod file_mode_builder;
-pub mod read_line;
\ No newline at end of file
+pub mod event;
+pub mod read_line;
+pub mod plugin_context;
+pub mod doctor_check;
@@ -0,0 +1,85 @@
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Outcome of a single doctor check
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DoctorStatus {
+	/// Nothing to do
+	Pass,
+	/// The project works but something should be looked at
+	Warn,
+	/// The project is broken
+	Fail,
+}
+
+/// Result of a single doctor check
+#[derive(Serialize, Clone, Debug)]
+pub struct DoctorCheck {
+	/// Name of the check
+	name: String,
+	/// Outcome of the check
+	status: DoctorStatus,
+	/// Human readable explanation of the outcome
+	details: String,
+	/// How to fix the problem, if any
+	hint: Option<String>,
+}
+json_serialize_to_string!(DoctorCheck);
+
+impl DoctorCheck {
+	/// Create a passing check
+	pub fn pass(name: impl Into<String>, details: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			status: DoctorStatus::Pass,
+			details: details.into(),
+			hint: None,
+		}
+	}
+
+	/// Create a check reporting a problem, along with the way to fix it
+	pub fn problem(name: impl Into<String>, status: DoctorStatus, details: impl Into<String>, hint: impl Into<String>) -> Self {
+		Self {
+			name: name.into(),
+			status,
+			details: details.into(),
+			hint: Some(hint.into()),
+		}
+	}
+
+	/// Get the name of the check
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Get the outcome of the check
+	pub fn status(&self) -> DoctorStatus {
+		self.status
+	}
+
+	/// Get the explanation of the outcome
+	pub fn details(&self) -> &str {
+		&self.details
+	}
+
+	/// Get the way to fix the problem
+	pub fn hint(&self) -> Option<&str> {
+		self.hint.as_deref()
+	}
+}
+
+/// Results of all the doctor checks
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DoctorReport {
+	pub checks: Vec<DoctorCheck>,
+}
+json_serialize_to_string!(DoctorReport);
+
+impl DoctorReport {
+	/// Count the checks with the given outcome
+	pub fn count(&self, status: DoctorStatus) -> usize {
+		self.checks.iter().filter(|check| check.status() == status).count()
+	}
+}
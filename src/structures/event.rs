@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Version of the JSON event schema, bumped on any breaking change of the [`Event`] shape
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Kind of a JSON event
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+	/// Log message emitted while the command runs, `data` holds the `message`
+	Log,
+	/// Last event of a run, `data` holds the structured output of the command, or its `error`
+	Result,
+}
+
+/// Single JSON event, printed as one line when running with `--json`
+#[derive(Serialize, Clone, Debug)]
+pub struct Event {
+	/// Version of the event schema
+	version: u32,
+	/// Kind of the event
+	event: EventKind,
+	/// Command being run, e.g. "make keys"
+	command: String,
+	/// Lowercase log level, e.g. "info"
+	level: String,
+	/// Payload of the event
+	data: serde_json::Value,
+	/// RFC 3339 timestamp of the event
+	ts: String,
+}
+json_serialize_to_string!(Event);
+
+impl Event {
+	/// Create a new event timestamped now
+	pub fn new(event: EventKind, command: impl Into<String>, level: log::Level, data: serde_json::Value) -> Self {
+		Self {
+			version: EVENT_SCHEMA_VERSION,
+			event,
+			command: command.into(),
+			level: level.as_str().to_lowercase(),
+			data,
+			ts: chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, false),
+		}
+	}
+}
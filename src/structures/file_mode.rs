@@ -1,13 +1,31 @@
-use std::ops::{BitAnd, BitOr, BitOrAssign};
+use std::fs::OpenOptions;
+
 use crate::structures::file_mode_builder::FileModeBuilder;
 
-#[derive(Debug, PartialEq)]
-pub enum FileMode {
-	None = 0b0000,
-	Read = 0b0001,
-	Write = 0b0010,
-	Create = 0b0100,
-	Truncate = 0b1000,
+/// Single flag a file can be opened with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileFlag {
+	/// File can be read
+	Read = 0b00_0001,
+	/// File can be written
+	Write = 0b00_0010,
+	/// Writes always go to the end of the file
+	Append = 0b00_0100,
+	/// File is created if it does not exist
+	Create = 0b00_1000,
+	/// File is created, failing if it already exists
+	CreateNew = 0b01_0000,
+	/// File is emptied when opened
+	Truncate = 0b10_0000,
+}
+
+/// Valid combination of flags a file is opened with, along with the unix permissions of the file
+/// when it gets created. Modes are built with [`FileMode::builder`], which refuses combinations
+/// that cannot be opened.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FileMode {
+	flags: u8,
+	permissions: Option<u32>,
 }
 
 impl FileMode {
@@ -15,181 +33,74 @@ impl FileMode {
 		FileModeBuilder::default()
 	}
 
-	/// Convert u8 to FileMode
-	pub fn from(mode: &u8) -> Self {
-		match mode {
-			0b0001 => FileMode::Read,
-			0b0010 => FileMode::Write,
-			0b0100 => FileMode::Create,
-			0b1000 => FileMode::Truncate,
-			_ => FileMode::None,
-		}
-	}
-}
-
-/// Implicitly convert FileMode to u8
-impl Into<u8> for FileMode {
-	fn into(self) -> u8 {
-		self as u8
+	/// Create a mode from already validated parts, see [`FileModeBuilder::build`]
+	pub(crate) fn new(flags: u8, permissions: Option<u32>) -> Self {
+		Self { flags, permissions }
 	}
-}
 
-/// Implement the equality operator for FileMode and u8
-/// # Example
-/// ```rust
-/// assert_eq!(FileMode::Read, 0b0001);
-/// assert_eq!(FileMode::Write, 0b0010);
-/// assert_eq!(FileMode::Create, 0b0100);
-/// assert_eq!(FileMode::Truncate, 0b1000);
-/// ```
-impl PartialEq<u8> for FileMode {
-	fn eq(&self, other: &u8) -> bool {
-		*self == FileMode::from(other)
+	/// Check whether the mode has the given flag
+	/// # Example
+	/// ```rust
+	/// # use saas_template_companion::structures::file_mode::{FileFlag, FileMode};
+	/// let mode = FileMode::builder().read().write().build().unwrap();
+	/// assert!(mode.contains(FileFlag::Read));
+	/// assert!(mode.contains(FileFlag::Write));
+	/// assert!(!mode.contains(FileFlag::Create));
+	/// ```
+	pub fn contains(&self, flag: FileFlag) -> bool {
+		self.flags & flag as u8 != 0
 	}
-}
 
-/// Implement the equality operator for FileMode and u8
-/// # Example
-/// ```rust
-/// assert_eq!(FileMode::Read, 0b0001);
-/// assert_eq!(FileMode::Write, 0b0010);
-/// assert_eq!(FileMode::Create, 0b0100);
-/// assert_eq!(FileMode::Truncate, 0b1000);
-/// ```
-impl PartialEq<FileMode> for u8 {
-	fn eq(&self, other: &FileMode) -> bool {
-		FileMode::from(self) == *other
+	/// Get the unix permissions of the file when it gets created, if any
+	pub fn permissions(&self) -> Option<u32> {
+		self.permissions
 	}
 }
 
-#[test]
-fn test_into_u8() {
-	assert_eq!(FileMode::Read, 0b0001);
-	assert_eq!(FileMode::Write, 0b0010);
-	assert_eq!(FileMode::Create, 0b0100);
-	assert_eq!(FileMode::Truncate, 0b1000);
-}
-
-/// Implement the bitwise and operator for FileMode and u8
+/// Convert the mode to the options opening the file
 /// # Example
-/// ```rust
-/// let mode = FileMode::builder().read().write().build();
-/// assert_eq!(FileMode::Read & mode, FileMode::Read);
-/// assert_eq!(FileMode::Write & mode, FileMode::Write);
-/// assert_eq!(FileMode::Create & mode, 0);
-/// assert_eq!(FileMode::Truncate & mode, 0);
+/// ```rust,no_run
+/// # use std::fs::OpenOptions;
+/// # use saas_template_companion::structures::file_mode::FileMode;
+/// let mode = FileMode::builder().append().create().private().build().unwrap();
+/// let file = OpenOptions::from(mode).open("history.log");
 /// ```
-impl BitAnd<u8> for FileMode {
-	type Output = u8;
-
-	fn bitand(self, rhs: u8) -> Self::Output {
-		(self as u8) & rhs
-	}
-}
-
-#[test]
-fn test_bitand_u8() {
-	let mode = FileMode::builder().read().write().build();
-	assert_eq!(FileMode::Read & mode, FileMode::Read);
-	assert_eq!(FileMode::Write & mode, FileMode::Write);
-	assert_eq!(FileMode::Create & mode, FileMode::None);
-	assert_eq!(FileMode::Truncate & mode, FileMode::None);
-}
-
-/// Implement the bitwise and operator for u8 and FileMode
-/// # Example
-/// ```rust
-/// let mode = FileMode::builder().read().write().build();
-/// assert_eq!(mode & FileMode::Read, FileMode::Read);
-/// assert_eq!(mode & FileMode::Write, FileMode::Write);
-/// assert_eq!(mode & FileMode::Create, 0);
-/// assert_eq!(mode & FileMode::Truncate, 0);
-/// ```
-impl BitAnd<FileMode> for u8 {
-	type Output = u8;
+impl From<FileMode> for OpenOptions {
+	fn from(mode: FileMode) -> OpenOptions {
+		let mut options = OpenOptions::new();
+		options.read(mode.contains(FileFlag::Read))
+		       .write(mode.contains(FileFlag::Write))
+		       .append(mode.contains(FileFlag::Append))
+		       .create(mode.contains(FileFlag::Create))
+		       .create_new(mode.contains(FileFlag::CreateNew))
+		       .truncate(mode.contains(FileFlag::Truncate));
+
+		// only applies to newly created files, existing ones keep their permissions
+		#[cfg(unix)]
+		if let Some(permissions) = mode.permissions() {
+			use std::os::unix::fs::OpenOptionsExt;
+			options.mode(permissions);
+		}
 
-	fn bitand(self, rhs: FileMode) -> Self::Output {
-		self & (rhs as u8)
+		options
 	}
 }
 
 #[test]
-fn test_bitand_file_mode() {
-	let mode = FileMode::builder().read().write().build();
-	assert_eq!(mode & FileMode::Read, FileMode::Read);
-	assert_eq!(mode & FileMode::Write, FileMode::Write);
-	assert_eq!(mode & FileMode::Create, FileMode::None);
-	assert_eq!(mode & FileMode::Truncate, FileMode::None);
-}
-
-/// Implement the bitwise or operator for FileModes
-/// # Example
-/// ```rust
-/// let mode = FileMode::builder().read().write().build();
-/// assert_eq!(FileMode::Read | FileMode::Write, mode);
-/// ```
-impl BitOr<FileMode> for FileMode {
-	type Output = u8;
+fn can_open_files_with_modes() {
+	use std::io::{Read, Write};
 
-	fn bitor(self, rhs: FileMode) -> Self::Output {
-		(self as u8) | (rhs as u8)
-	}
-}
+	let directory = assert_fs::TempDir::new().unwrap();
+	let path = directory.path().join("history.log");
 
-#[test]
-fn test_bitor_file_mode() {
-	let mode = FileMode::builder().read().write().build();
-	assert_eq!(FileMode::Read | FileMode::Write, mode);
-}
+	let append = FileMode::builder().append().create_new().build().unwrap();
+	OpenOptions::from(append).open(&path).unwrap().write_all(b"first\n").unwrap();
+	assert!(OpenOptions::from(append).open(&path).is_err());
 
-/// Implement the bitwise or operator for FileModes
-/// # Example
-/// ```rust
-/// let mode = FileMode::builder().read().write().build();
-/// assert_eq!(FileMode::Read | FileMode::Write, mode);
-/// ```
-impl BitOr<FileMode> for u8 {
-	type Output = u8;
+	let append = FileMode::builder().append().build().unwrap();
+	OpenOptions::from(append).open(&path).unwrap().write_all(b"second\n").unwrap();
 
-	fn bitor(self, rhs: FileMode) -> Self::Output {
-		self | (rhs as u8)
-	}
-}
-
-#[test]
-fn test_bitor_u8() {
-	let mode = FileMode::builder().read().write().create().build();
-	assert_eq!(FileMode::Read | FileMode::Write | FileMode::Create, mode);
+	let mut content = String::new();
+	OpenOptions::from(FileMode::builder().read().build().unwrap()).open(&path).unwrap().read_to_string(&mut content).unwrap();
+	assert_eq!(content, "first\nsecond\n");
 }
-
-/// Implement the bitwise or assignment operator for FileMode and u8
-/// # Example
-/// ```rust
-/// let mut mode = FileMode::None;
-/// mode |= FileMode::Read;
-/// assert_eq!(mode, FileMode::Read);
-/// mode |= FileMode::Write;
-/// assert_eq!(mode, FileMode::Read | FileMode::Write);
-/// mode |= FileMode::Create;
-/// assert_eq!(mode, FileMode::Read | FileMode::Write | FileMode::Create);
-/// mode |= FileMode::Truncate;
-/// assert_eq!(mode, FileMode::Read | FileMode::Write | FileMode::Create | FileMode::Truncate);
-/// ```
-impl BitOrAssign<FileMode> for u8 {
-	fn bitor_assign(&mut self, rhs: FileMode) {
-		*self |= rhs as u8;
-	}
-}
-
-#[test]
-fn test_bitor_assign_file_mode() {
-	let mut mode = FileMode::None as u8;
-	mode |= FileMode::Read;
-	assert_eq!(mode, FileMode::Read);
-	mode |= FileMode::Write;
-	assert_eq!(mode, FileMode::Read | FileMode::Write);
-	mode |= FileMode::Create;
-	assert_eq!(mode, FileMode::Read | FileMode::Write | FileMode::Create);
-	mode |= FileMode::Truncate;
-	assert_eq!(mode, FileMode::Read | FileMode::Write | FileMode::Create | FileMode::Truncate);
-}
\ No newline at end of file
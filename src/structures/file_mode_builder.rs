@@ -1,103 +1,124 @@
-use crate::structures::file_mode::FileMode;
+use crate::structures::file_mode::{FileFlag, FileMode};
 
+/// Permissions of the files holding secrets, readable and writable by their owner only
+pub const PRIVATE_PERMISSIONS: u32 = 0o600;
+
+#[derive(Default)]
 pub struct FileModeBuilder {
-	mode: u8,
+	flags: u8,
+	permissions: Option<u32>,
 }
 
 impl FileModeBuilder {
-	/// Create a new builder with the default mode
-	pub fn default() -> Self {
-		Self {
-			mode: FileMode::None as u8,
-		}
+	/// Add a flag to the mode
+	fn flag(mut self, flag: FileFlag) -> Self {
+		self.flags |= flag as u8;
+		self
 	}
 
-	/// Create a new builder with the given mode
-	pub fn from(mode: u8) -> Self {
-		Self {
-			mode,
-		}
+	/// Check whether the flag was added
+	fn has(&self, flag: FileFlag) -> bool {
+		self.flags & flag as u8 != 0
 	}
 
 	/// File can be read
-	pub fn read(mut self) -> Self {
-		self.mode |= FileMode::Read;
-		self
+	pub fn read(self) -> Self {
+		self.flag(FileFlag::Read)
 	}
 
 	/// File can be written
-	pub fn write(mut self) -> Self {
-		self.mode |= FileMode::Write;
-		self
+	pub fn write(self) -> Self {
+		self.flag(FileFlag::Write)
 	}
 
-	/// File can be created
-	pub fn create(mut self) -> Self {
-		self.mode |= FileMode::Create;
-		self
+	/// File is written at its end
+	pub fn append(self) -> Self {
+		self.flag(FileFlag::Append)
 	}
 
-	/// File can be truncated
-	pub fn truncate(mut self) -> Self {
-		self.mode |= FileMode::Truncate;
-		self
+	/// File can be created
+	pub fn create(self) -> Self {
+		self.flag(FileFlag::Create)
 	}
 
-	/// Build the mode
-	pub fn build(self) -> u8 {
-		self.mode
+	/// File must be created, opening an existing file fails
+	pub fn create_new(self) -> Self {
+		self.flag(FileFlag::CreateNew)
 	}
 
-	/// Check if the file can be read
-	pub fn can_read(&self) -> bool {
-		self.mode & FileMode::Read == FileMode::Read
+	/// File can be truncated
+	pub fn truncate(self) -> Self {
+		self.flag(FileFlag::Truncate)
 	}
 
-	/// Check if the file can be written
-	pub fn can_write(&self) -> bool {
-		self.mode & FileMode::Write == FileMode::Write
+	/// File is created with the given unix permissions, e.g. `0o640`
+	pub fn permissions(mut self, permissions: u32) -> Self {
+		self.permissions = Some(permissions);
+		self
 	}
 
-	/// Check if the file can be created
-	pub fn can_create(&self) -> bool {
-		self.mode & FileMode::Create == FileMode::Create
+	/// File is created readable and writable by its owner only
+	pub fn private(self) -> Self {
+		self.permissions(PRIVATE_PERMISSIONS)
 	}
 
-	/// Check if the file can be truncated
-	pub fn can_truncate(&self) -> bool {
-		self.mode & FileMode::Truncate == FileMode::Truncate
+	/// Build the mode, failing for the combinations a file cannot be opened with
+	pub fn build(self) -> anyhow::Result<FileMode> {
+		let writes = self.has(FileFlag::Write) || self.has(FileFlag::Append);
+
+		if !self.has(FileFlag::Read) && !writes {
+			anyhow::bail!("A file mode must at least read, write or append");
+		}
+		if self.has(FileFlag::Truncate) && (!self.has(FileFlag::Write) || self.has(FileFlag::Append)) {
+			anyhow::bail!("A file can only be truncated when written, and not appended to");
+		}
+		if (self.has(FileFlag::Create) || self.has(FileFlag::CreateNew)) && !writes {
+			anyhow::bail!("A file can only be created when written or appended to");
+		}
+		if self.has(FileFlag::Create) && self.has(FileFlag::CreateNew) {
+			anyhow::bail!("A file mode cannot both create and create a new file");
+		}
+		if let Some(permissions) = self.permissions {
+			if !self.has(FileFlag::Create) && !self.has(FileFlag::CreateNew) {
+				anyhow::bail!("Permissions only apply to the files being created");
+			}
+			if permissions & !0o7777 != 0 {
+				anyhow::bail!("Invalid file permissions {:o}", permissions);
+			}
+		}
+
+		Ok(FileMode::new(self.flags, self.permissions))
 	}
 }
 
 #[test]
 fn test_file_modes_building() {
-	let mode = FileMode::builder().read().write().build();
-	assert_eq!(FileMode::Read & mode, FileMode::Read);
-	assert_eq!(FileMode::Write & mode, FileMode::Write);
-	assert_eq!(FileMode::Create & mode, FileMode::None);
-	assert_eq!(FileMode::Truncate & mode, FileMode::None);
-
-	let mode = FileMode::builder().read().build();
-	assert_eq!(FileMode::Read & mode, FileMode::Read);
-	assert_eq!(FileMode::Write & mode, FileMode::None);
-	assert_eq!(FileMode::Create & mode, FileMode::None);
-	assert_eq!(FileMode::Truncate & mode, FileMode::None);
-
-	let mode = FileMode::builder().write().build();
-	assert_eq!(FileMode::Read & mode, FileMode::None);
-	assert_eq!(FileMode::Write & mode, FileMode::Write);
-	assert_eq!(FileMode::Create & mode, FileMode::None);
-	assert_eq!(FileMode::Truncate & mode, FileMode::None);
-
-	let mode = FileMode::builder().create().build();
-	assert_eq!(FileMode::Read & mode, FileMode::None);
-	assert_eq!(FileMode::Write & mode, FileMode::None);
-	assert_eq!(FileMode::Create & mode, FileMode::Create);
-	assert_eq!(FileMode::Truncate & mode, FileMode::None);
-
-	let mode = FileMode::builder().truncate().build();
-	assert_eq!(FileMode::Read & mode, FileMode::None);
-	assert_eq!(FileMode::Write & mode, FileMode::None);
-	assert_eq!(FileMode::Create & mode, FileMode::None);
-	assert_eq!(FileMode::Truncate & mode, FileMode::Truncate);
-}
\ No newline at end of file
+	let mode = FileMode::builder().read().write().build().unwrap();
+	assert!(mode.contains(FileFlag::Read));
+	assert!(mode.contains(FileFlag::Write));
+	assert!(!mode.contains(FileFlag::Create));
+	assert!(!mode.contains(FileFlag::Truncate));
+	assert_eq!(mode.permissions(), None);
+
+	let mode = FileMode::builder().write().create().truncate().private().build().unwrap();
+	assert!(mode.contains(FileFlag::Write));
+	assert!(mode.contains(FileFlag::Create));
+	assert!(mode.contains(FileFlag::Truncate));
+	assert!(!mode.contains(FileFlag::Read));
+	assert_eq!(mode.permissions(), Some(0o600));
+
+	let mode = FileMode::builder().append().create().build().unwrap();
+	assert!(mode.contains(FileFlag::Append));
+	assert!(!mode.contains(FileFlag::Write));
+}
+
+#[test]
+fn cannot_build_nonsense_modes() {
+	assert!(FileMode::builder().build().is_err());
+	assert!(FileMode::builder().create().build().is_err());
+	assert!(FileMode::builder().read().truncate().build().is_err());
+	assert!(FileMode::builder().append().truncate().build().is_err());
+	assert!(FileMode::builder().write().create().create_new().build().is_err());
+	assert!(FileMode::builder().write().private().build().is_err());
+	assert!(FileMode::builder().write().create().permissions(0o10_0644).build().is_err());
+}
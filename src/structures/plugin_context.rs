@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+use crate::json_serialize_to_string;
+
+/// Version of the JSON context schema, bumped on any breaking change of the [`PluginContext`] shape
+pub const PLUGIN_CONTEXT_VERSION: u32 = 1;
+
+/// Global arguments of the companion, as seen by an external subcommand
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginGlobalArgs {
+	/// Whether the run must not apply any modification
+	pub dry_run: bool,
+	/// Whether the messages must be logged as json objects
+	pub json: bool,
+	/// Lowercase maximum log level, e.g. "info" or "off"
+	pub log_level: String,
+	/// File the log messages are also written to
+	pub log_file: Option<String>,
+}
+
+/// JSON context written to the standard input of an external subcommand
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginContext {
+	/// Version of the context schema
+	version: u32,
+	/// Name of the external subcommand, e.g. "stripe"
+	command: String,
+	/// Arguments given after the name of the subcommand
+	args: Vec<String>,
+	/// Global arguments of the companion
+	global_args: PluginGlobalArgs,
+	/// Version of the companion running the subcommand
+	companion_version: String,
+	/// Working directory of the run
+	cwd: String,
+}
+json_serialize_to_string!(PluginContext);
+
+impl PluginContext {
+	/// Create a new context for the given subcommand
+	pub fn new(command: impl Into<String>, args: Vec<String>, global_args: PluginGlobalArgs, cwd: impl Into<String>) -> Self {
+		Self {
+			version: PLUGIN_CONTEXT_VERSION,
+			command: command.into(),
+			args,
+			global_args,
+			companion_version: clap::crate_version!().to_owned(),
+			cwd: cwd.into(),
+		}
+	}
+
+	/// Get the global arguments of the companion
+	pub fn global_args(&self) -> &PluginGlobalArgs {
+		&self.global_args
+	}
+}
@@ -1,5 +1,4 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 use anyhow::Context;
 
 pub struct ReadLine {
@@ -9,8 +8,8 @@ pub struct ReadLine {
 }
 
 impl ReadLine {
-	/// Read a line from the file
-	pub fn from(reader: &mut BufReader<File>) -> anyhow::Result<Self> {
+	/// Read a line from the source
+	pub fn from(reader: &mut impl BufRead) -> anyhow::Result<Self> {
 		let mut line = String::new();
 
 		let length = reader.read_line(&mut line)
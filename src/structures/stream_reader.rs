@@ -1,65 +1,85 @@
 use std::fs::{File, OpenOptions};
-use std::io::BufReader;
+use std::io::{BufReader, Read, Stdin};
 
 use anyhow::Context;
 
-use crate::structures::file_mode_builder::FileModeBuilder;
+use crate::structures::file_mode::FileMode;
 use crate::structures::read_line::ReadLine;
 
-pub struct StreamReader<'a> {
-	filename: &'a str,
-	file: File,
-	pub reader: BufReader<File>,
+/// Line by line reader over a file, stdin, an in-memory buffer or any other source
+pub struct StreamReader<R: Read = File> {
+	name: String,
+	pub reader: BufReader<R>,
 }
 
-impl<'a> StreamReader<'a> {
+impl StreamReader<File> {
 	/// Create a new StreamReader instance
 	/// # Example
-	/// ```rust
+	/// ```rust,no_run
+	/// # use anyhow::Context;
+	/// # use saas_template_companion::structures::{file_mode::FileMode, stream_reader::StreamReader};
+	/// # fn main() -> anyhow::Result<()> {
 	/// let mut stream_reader = StreamReader::new(
 	///     ".env",
-	///     FileMode::builder().write().read().create().build(),
+	///     FileMode::builder().write().read().create().build()?,
 	/// ).with_context(|| format!("Failed to open stream reader to .env"))?;
+	/// # Ok(())
+	/// # }
 	/// ```
-	pub fn new(filename: &'a str, mode: u8) -> anyhow::Result<Self> {
+	pub fn new(filename: &str, mode: FileMode) -> anyhow::Result<Self> {
 		let cwd = std::env::current_dir()
 			.with_context(|| "Failed to get the current working directory")?;
 
-		let mode = FileModeBuilder::from(mode);
-
-		let file = OpenOptions::new()
-			.write(mode.can_write())
-			.read(mode.can_read())
-			.create(mode.can_create())
-			.truncate(mode.can_truncate())
+		let file = OpenOptions::from(mode)
 			.open(filename)
 			.with_context(|| format!("Failed to open file '{}' (current working directory: {})", filename, cwd.display()))?;
 
-		let reader = BufReader::new(file.try_clone().with_context(|| format!("Cannot clone file handler to {}", filename))?);
+		Ok(Self::from_reader(filename, file))
+	}
 
-		Ok(Self {
-			filename,
-			reader,
-			file,
-		})
+	/// Get the raw file descriptor
+	pub fn file(&self) -> &File {
+		self.reader.get_ref()
 	}
+}
 
-	/// Read a line from the file
+impl StreamReader<Stdin> {
+	/// Create a StreamReader reading the standard input
+	pub fn stdin() -> Self {
+		Self::from_reader("stdin", std::io::stdin())
+	}
+}
+
+impl<R: Read> StreamReader<R> {
+	/// Create a StreamReader over any source, the name is only used to describe the source in errors
+	/// # Example
+	/// ```rust
+	/// # use saas_template_companion::structures::stream_reader::StreamReader;
+	/// let mut stream_reader = StreamReader::from_reader("buffer", "NAME=value\n".as_bytes());
+	/// assert_eq!(stream_reader.read_line().unwrap().line(), "NAME=value\n");
+	/// ```
+	pub fn from_reader(name: impl Into<String>, reader: R) -> Self {
+		Self {
+			name: name.into(),
+			reader: BufReader::new(reader),
+		}
+	}
+
+	/// Read a line from the source
 	pub fn read_line(&mut self) -> anyhow::Result<ReadLine> {
 		ReadLine::from(&mut self.reader)
-			.with_context(|| format!("Failed to read line from file '{}'", self.filename))
+			.with_context(|| format!("Failed to read line from '{}'", self.name))
 	}
 
-	/// Get the raw file descriptor
-	pub fn file(&self) -> &File {
-		&self.file
+	/// Get the name of the source
+	pub fn name(&self) -> &str {
+		&self.name
 	}
 }
 
 #[test]
 fn can_read_lines_one_by_one() {
 	use assert_fs::prelude::*;
-	use crate::structures::file_mode::FileMode;
 
 	// create a temporary file
 	let file = assert_fs::NamedTempFile::new(".env").unwrap();
@@ -69,10 +89,20 @@ fn can_read_lines_one_by_one() {
 		file.path().to_str().unwrap(),
 		FileMode::builder()
 			.read()
-			.build(),
+			.build()
+			.unwrap(),
 	).unwrap();
 
 	assert_eq!(stream_reader.read_line().unwrap().line(), "LINE_1=1\n");
 	assert_eq!(stream_reader.read_line().unwrap().line(), "LINE_2=this-is-simple\n");
 	assert_eq!(stream_reader.read_line().unwrap().line(), "LINE_3=\"This is pretty complex\"");
-}
\ No newline at end of file
+}
+
+#[test]
+fn can_read_lines_from_memory() {
+	let mut stream_reader = StreamReader::from_reader("buffer", std::io::Cursor::new("FIRST=1\nSECOND=2"));
+
+	assert_eq!(stream_reader.read_line().unwrap().line(), "FIRST=1\n");
+	assert_eq!(stream_reader.read_line().unwrap().line(), "SECOND=2");
+	assert!(stream_reader.read_line().unwrap().eof());
+}
@@ -0,0 +1,83 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Stdout, Write};
+
+use anyhow::Context;
+
+use crate::structures::file_mode::FileMode;
+
+/// Writer to a file, stdout, an in-memory buffer or any other destination
+pub struct StreamWriter<W: Write = File> {
+	name: String,
+	writer: W,
+}
+
+impl StreamWriter<File> {
+	/// Create a new StreamWriter instance
+	/// # Example
+	/// ```rust,no_run
+	/// # use anyhow::Context;
+	/// # use saas_template_companion::structures::{file_mode::FileMode, stream_writer::StreamWriter};
+	/// # fn main() -> anyhow::Result<()> {
+	/// let mut stream_writer = StreamWriter::new(
+	///     ".env",
+	///     FileMode::builder().write().create().truncate().private().build()?,
+	/// ).with_context(|| format!("Failed to open stream writer to .env"))?;
+	/// stream_writer.write_all(b"NAME=\"value\"\n")?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub fn new(filename: &str, mode: FileMode) -> anyhow::Result<Self> {
+		let cwd = std::env::current_dir()
+			.with_context(|| "Failed to get the current working directory")?;
+
+		let file = OpenOptions::from(mode)
+			.open(filename)
+			.with_context(|| format!("Failed to open file '{}' (current working directory: {})", filename, cwd.display()))?;
+
+		Ok(Self::from_writer(filename, file))
+	}
+}
+
+impl StreamWriter<Stdout> {
+	/// Create a StreamWriter writing to the standard output
+	pub fn stdout() -> Self {
+		Self::from_writer("stdout", std::io::stdout())
+	}
+}
+
+impl<W: Write> StreamWriter<W> {
+	/// Create a StreamWriter over any destination, the name is only used to describe it in errors
+	pub fn from_writer(name: impl Into<String>, writer: W) -> Self {
+		Self {
+			name: name.into(),
+			writer,
+		}
+	}
+
+	/// Write the whole content and flush it
+	pub fn write_all(&mut self, content: &[u8]) -> anyhow::Result<()> {
+		self.writer
+		    .write_all(content)
+		    .and_then(|_| self.writer.flush())
+		    .with_context(|| format!("Cannot write to {}, does the file allow writing?", self.name))
+	}
+
+	/// Get the name of the destination
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Get back the underlying destination
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+}
+
+#[test]
+fn can_write_to_memory() {
+	let mut stream_writer = StreamWriter::from_writer("buffer", Vec::new());
+	stream_writer.write_all(b"FIRST=1\n").unwrap();
+	stream_writer.write_all(b"SECOND=2\n").unwrap();
+
+	assert_eq!(stream_writer.into_inner(), b"FIRST=1\nSECOND=2\n");
+}
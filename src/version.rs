@@ -1,6 +1,15 @@
 use alkali::{SODIUM_LIBRARY_VERSION_MAJOR, SODIUM_LIBRARY_VERSION_MINOR};
 
-pub fn handle() -> anyhow::Result<()> {
+use crate::global_args;
+
+pub fn handle(global_arguments: &global_args::GlobalArgs) -> anyhow::Result<()> {
+	if global_arguments.json {
+		log_mdc::insert("name", clap::crate_name!());
+		log_mdc::insert("version", clap::crate_version!());
+		log_mdc::insert("sodium_version", format!("{}.{}", SODIUM_LIBRARY_VERSION_MAJOR, SODIUM_LIBRARY_VERSION_MINOR));
+		return Ok(());
+	}
+
 	println!(
 		"{} v{} - with\n\t- Sodium v{}.{}",
 		clap::crate_name!(),
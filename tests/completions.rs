@@ -0,0 +1,22 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn can_generate_shell_completions() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["completions", "zsh"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("#compdef saas-template-companion"))
+	   .stdout(predicate::str::contains("signatures"));
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["completions", "fish"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("complete -c saas-template-companion"));
+
+	Ok(())
+}
@@ -0,0 +1,81 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn can_diagnose_project() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child(".env").touch().unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["make", "keys"]);
+	cmd.assert()
+	   .success();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["doctor"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("All the keys are valid"))
+	   .stdout(predicate::str::contains("saas-template-companion init"))
+	   .stderr(predicate::str::contains("0 failed"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_diagnose_broken_project() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("companion.toml").write_str("env = 42\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["doctor", "--json"]);
+	let output = cmd.output()?;
+	assert_eq!(output.status.code(), Some(saas_template_companion::error::VALIDATION_FAILED));
+
+	let stdout = String::from_utf8(output.stdout)?;
+	let result: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap())?;
+	let checks = result["data"]["doctor"]["checks"].as_array().unwrap();
+	assert_eq!(checks[0]["name"], "Configuration");
+	assert_eq!(checks[0]["status"], "fail");
+	assert_eq!(checks[1]["status"], "fail");
+	assert!(checks[1]["hint"].as_str().unwrap().contains("init"));
+
+	Ok(())
+}
+
+#[test]
+fn can_diagnose_project_with_given_configuration() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("config/companion.toml").write_str("env = \"apps/web/.env\"\n").unwrap();
+	directory.child("apps/web/.env").touch().unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["make", "keys", "--env", "apps/web/.env"]);
+	cmd.assert()
+	   .success();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["doctor", "--config", "config/companion.toml", "--json"]);
+	let output = cmd.output()?;
+	assert!(output.status.success());
+
+	let stdout = String::from_utf8(output.stdout)?;
+	let result: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap())?;
+	let checks = result["data"]["doctor"]["checks"].as_array().unwrap();
+	assert_eq!(checks[0]["status"], "pass");
+	assert_eq!(checks[1]["status"], "pass");
+	assert!(checks[1]["details"].as_str().unwrap().contains("apps/web/.env"));
+	let signatures = checks.iter().find(|check| check["name"] == "Procedure signatures").unwrap();
+	assert_eq!(signatures["status"], "warn");
+	assert_eq!(signatures["details"], "Signatures not checked: make signatures is not implemented");
+
+	Ok(())
+}
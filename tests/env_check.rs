@@ -0,0 +1,65 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+const SCHEMA: &str = r#"
+[variables.DATABASE_URL]
+type = "url"
+required = true
+description = "Connection string of the database"
+
+[variables.GITHUB_SECRET]
+type = "string"
+required = ["production"]
+
+[variables.PORT]
+type = "port"
+"#;
+
+#[test]
+fn can_check_env_against_schema() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("NODE_ENV=development\nDATABASE_URL=\"postgres://localhost/db\"\nPORT=3000\n").unwrap();
+	let schema = assert_fs::NamedTempFile::new("env.schema.toml").unwrap();
+	schema.write_str(SCHEMA).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "check", "--env", env.path().to_str().unwrap(), "--schema", schema.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("Connection string of the database"))
+	   .stdout(predicate::str::contains("for development"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_check_env_with_missing_or_invalid_values() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"localhost:5432/db\"\nPORT=3000\n").unwrap();
+	let schema = assert_fs::NamedTempFile::new("env.schema.json").unwrap();
+	schema.write_str(r#"{
+		"variables": {
+			"DATABASE_URL": { "type": "url", "required": true },
+			"GITHUB_SECRET": { "type": "string", "required": ["production"] }
+		}
+	}"#).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args([
+		"env", "check",
+		"--env", env.path().to_str().unwrap(),
+		"--schema", schema.path().to_str().unwrap(),
+		"--node-env", "production",
+	]);
+	cmd.assert()
+	   .code(saas_template_companion::error::VALIDATION_FAILED)
+	   .stdout(predicate::str::contains("Invalid"))
+	   .stdout(predicate::str::contains("Missing"))
+	   .stdout(predicate::str::contains("Undeclared"))
+	   .stderr(predicate::str::contains("[ERROR]"));
+
+	Ok(())
+}
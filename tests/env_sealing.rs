@@ -0,0 +1,80 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn can_seal_and_unseal_values() -> Result<(), Box<dyn std::error::Error>> {
+	let keys = assert_fs::NamedTempFile::new(".env.keys").unwrap();
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"postgres://localhost/db\"\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", keys.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	let mut cmd = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "seal", "STRIPE_SECRET", "--env", env.path().to_str().unwrap(), "--key-file", keys.path().to_str().unwrap()]);
+	cmd.write_stdin("sk_live_secret\n");
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] STRIPE_SECRET sealed into"));
+
+	env.assert(predicate::str::contains("STRIPE_SECRET=\"sealed:"))
+	   .assert(predicate::str::contains("sk_live_secret").not());
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "unseal", "STRIPE_SECRET", "--print", "--env", env.path().to_str().unwrap(), "--key-file", keys.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("STRIPE_SECRET=\"sk_live_secret\""));
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "unseal", "--env", env.path().to_str().unwrap(), "--key-file", keys.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	env.assert(predicate::str::contains("STRIPE_SECRET=\"sk_live_secret\""))
+	   .assert(predicate::str::contains("DATABASE_URL=\"postgres://localhost/db\""));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_seal_without_public_key() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"postgres://localhost/db\"\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "seal", "STRIPE_SECRET", "--value", "sk_live_secret", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .failure()
+	   .stderr(predicate::str::contains("run `make keys` first"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_seal_empty_stdin_value() -> Result<(), Box<dyn std::error::Error>> {
+	let keys = assert_fs::NamedTempFile::new(".env.keys").unwrap();
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"postgres://localhost/db\"\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", keys.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	let mut cmd = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "seal", "STRIPE_SECRET", "--env", env.path().to_str().unwrap(), "--key-file", keys.path().to_str().unwrap()]);
+	cmd.write_stdin("");
+	cmd.assert()
+	   .code(saas_template_companion::error::USER_ABORTED)
+	   .stderr(predicate::str::contains("No value given on stdin, nothing sealed"));
+
+	env.assert("DATABASE_URL=\"postgres://localhost/db\"\n");
+
+	Ok(())
+}
@@ -0,0 +1,125 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+use saas_template_companion::make::keys::constants::{
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+	ENV_VARIABLE__NEXTAUTH_SECRET,
+};
+
+#[test]
+fn can_diff_env_against_example() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"postgres://localhost/db\"\nLOCAL_ONLY=1\n").unwrap();
+	let example = assert_fs::NamedTempFile::new(".env.example").unwrap();
+	example.write_str("DATABASE_URL=\"\"\nPORT=3000\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "diff", "--env", env.path().to_str().unwrap(), "--example", example.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("PORT"))
+	   .stdout(predicate::str::contains("LOCAL_ONLY"))
+	   .stderr(predicate::str::contains("[WARN] 1 variables missing from"));
+
+	Ok(())
+}
+
+#[test]
+fn can_sync_env_with_example() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"postgres://localhost/db\"\n").unwrap();
+	let example = assert_fs::NamedTempFile::new(".env.example").unwrap();
+	example.write_str(&format!(
+		"DATABASE_URL=\"\"\nPORT=3000\n{}=\"\"\n{}=\"\"\n{}=\"\"\n",
+		ENV_VARIABLE__NEXTAUTH_SECRET,
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+	)).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "sync", "--env", env.path().to_str().unwrap(), "--example", example.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] Adding PORT with the example value"))
+	   .stdout(predicate::str::contains(format!("[INFO] Adding {} with a freshly generated value", ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	env.assert(predicate::str::starts_with("DATABASE_URL=\"postgres://localhost/db\"\nPORT=\"3000\"\n"))
+	   .assert(predicate::str::contains(format!("{}=\"\"", ENV_VARIABLE__NEXTAUTH_SECRET)).not());
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--verify", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	Ok(())
+}
+
+#[test]
+fn cannot_diff_env_against_missing_example() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	let example = assert_fs::NamedTempFile::new(".env.example").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "diff", "--env", env.path().to_str().unwrap(), "--example", example.path().to_str().unwrap()]);
+	cmd.assert()
+	   .code(exitcode::NOINPUT)
+	   .stderr(predicate::str::contains("does not exist"));
+
+	Ok(())
+}
+
+#[test]
+fn can_sync_env_recovering_missing_public_key() -> Result<(), Box<dyn std::error::Error>> {
+	use saas_template_companion::helpers::base64_url;
+
+	let keypair = alkali::asymmetric::kx::Keypair::generate()?;
+	let private_key = base64_url(keypair.private_key.as_slice())?;
+	let public_key = base64_url(keypair.public_key.as_slice())?;
+
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str(&format!("{}=\"{}\"\n", ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY, private_key)).unwrap();
+	let example = assert_fs::NamedTempFile::new(".env.example").unwrap();
+	example.write_str(&format!(
+		"{}=\"\"\n{}=\"\"\n",
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+	)).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "sync", "--env", env.path().to_str().unwrap(), "--example", example.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] 1 variables added"));
+
+	env.assert(format!(
+		"{}=\"{}\"\n{}=\"{}\"\n",
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		private_key,
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		public_key,
+	));
+
+	Ok(())
+}
+
+#[test]
+fn can_sync_env_with_example_from_stdin() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("DATABASE_URL=\"postgres://localhost/db\"\n").unwrap();
+
+	let mut cmd = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "sync", "--env", env.path().to_str().unwrap(), "--example", "-"]);
+	cmd.write_stdin("DATABASE_URL=\"\"\nPORT=3000\n");
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::is_empty());
+
+	env.assert(predicate::str::contains("DATABASE_URL=\"postgres://localhost/db\""))
+	   .assert(predicate::str::contains("PORT=\"3000\""));
+
+	Ok(())
+}
@@ -0,0 +1,187 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn can_set_get_and_unset_variables() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("# database\nDATABASE_URL=\"postgres://localhost/db\"\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "set", "STRIPE_SECRET=sk_test_secret", "PORT=3000", "--file", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] STRIPE_SECRET set in"));
+
+	env.assert("# database\nDATABASE_URL=\"postgres://localhost/db\"\nSTRIPE_SECRET=\"sk_test_secret\"\nPORT=\"3000\"\n");
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "get", "STRIPE_SECRET", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::diff("sk_test_secret\n"));
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "unset", "STRIPE_SECRET", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	env.assert("# database\nDATABASE_URL=\"postgres://localhost/db\"\nPORT=\"3000\"\n");
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "get", "STRIPE_SECRET", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .failure();
+
+	Ok(())
+}
+
+#[test]
+fn can_set_variables_without_tracing_their_values() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("PORT=3000\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "set", "STRIPE_SECRET=sk_test_secret", "-vvv", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[TRACE]"))
+	   .stdout(predicate::str::contains("sk_test_secret").not())
+	   .stderr(predicate::str::contains("sk_test_secret").not());
+
+	Ok(())
+}
+
+#[test]
+fn cannot_set_variables_with_invalid_names() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("PORT=3000\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "set", "#X=2", "BAD NAME=1", "GOOD=1", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .code(saas_template_companion::error::VALIDATION_FAILED)
+	   .stderr(predicate::str::contains("Invalid variable name(s) #X, BAD NAME"));
+
+	env.assert("PORT=3000\n");
+
+	Ok(())
+}
+
+#[test]
+fn can_set_variables_in_dry_run_mode() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("PORT=3000\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "set", "PORT=4000", "--dry-run", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stderr(predicate::str::contains("[WARN] Dry run, skipping file update"));
+
+	env.assert("PORT=3000\n");
+
+	Ok(())
+}
+
+#[test]
+fn can_list_masked_variables() -> Result<(), Box<dyn std::error::Error>> {
+	let env = assert_fs::NamedTempFile::new(".env").unwrap();
+	env.write_str("STRIPE_SECRET=sk_test_very_secret_value\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "list", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("STRIPE_SECRET"))
+	   .stdout(predicate::str::contains("sk_t********"))
+	   .stdout(predicate::str::contains("sk_test_very_secret_value").not());
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["env", "list", "--reveal", "--env", env.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("sk_test_very_secret_value"));
+
+	Ok(())
+}
+
+#[test]
+fn can_use_configured_env_file() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("companion.toml").write_str("env = \"apps/web/.env\"\n").unwrap();
+	directory.child("apps/web/.env").write_str("PORT=3000\n").unwrap();
+	directory.child("other.toml").write_str("env = \"other.env\"\n").unwrap();
+	directory.child("other.env").write_str("PORT=4000\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["env", "get", "PORT"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::diff("3000\n"));
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["env", "get", "PORT", "--config", "other.toml"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::diff("4000\n"));
+
+	// the option given on the command line takes precedence
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["env", "get", "PORT", "--env", "other.env"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::diff("4000\n"));
+
+	Ok(())
+}
+
+#[test]
+fn can_ignore_invalid_discovered_configuration() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("companion.toml").write_str("unknown = true\n").unwrap();
+	directory.child(".env").write_str("PORT=3000\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["env", "get", "PORT"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::diff("3000\n"))
+	   .stderr(predicate::str::contains("Invalid configuration companion.toml"))
+	   .stderr(predicate::str::contains("using the default settings"));
+
+	// a configuration given explicitly must be valid
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["env", "get", "PORT", "--config", "companion.toml"]);
+	cmd.assert()
+	   .code(exitcode::CONFIG)
+	   .stderr(predicate::str::contains("Invalid configuration companion.toml"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_read_or_unset_variables_of_missing_file() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+
+	for arguments in [vec!["env", "list"], vec!["env", "get", "FOO"], vec!["env", "unset", "FOO"]] {
+		let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+		cmd.current_dir(directory.path())
+		   .args(arguments)
+		   .args(["--env", "missing.env"]);
+		cmd.assert()
+		   .code(exitcode::NOINPUT)
+		   .stderr(predicate::str::contains("missing.env does not exist"));
+	}
+
+	directory.child("missing.env").assert(predicate::path::missing());
+
+	Ok(())
+}
@@ -0,0 +1,69 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+use saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET;
+
+#[test]
+fn can_init_project() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("package.json").write_str("{}").unwrap();
+	directory.child("yarn.lock").touch().unwrap();
+	directory.child(".env.example")
+	         .write_str("DATABASE_URL=\"\"\nNEXTAUTH_SECRET=\"\"\nASYMMETRIC_ENCRYPTION_PUBLIC_KEY=\"\"\nASYMMETRIC_ENCRYPTION_PRIVATE_KEY=\"\"\n")
+	         .unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["init"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] SaaS template detected in"))
+	   .stdout(predicate::str::contains("[ ] Set DATABASE_URL in .env"))
+	   .stdout(predicate::str::contains("[ ] Install the dependencies with `yarn install`"));
+
+	directory.child("companion.toml").assert(predicate::str::contains("env = \".env\""));
+	let values = std::fs::read_to_string(directory.child(".env").path())?;
+	assert!(!values.contains(&format!("{}=\"\"", ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	Ok(())
+}
+
+#[test]
+fn can_init_project_over_invalid_configuration() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("package.json").write_str("{}").unwrap();
+	directory.child(".env.example")
+	         .write_str("NEXTAUTH_SECRET=\"\"\nASYMMETRIC_ENCRYPTION_PUBLIC_KEY=\"\"\nASYMMETRIC_ENCRYPTION_PRIVATE_KEY=\"\"\n")
+	         .unwrap();
+	directory.child("companion.toml").write_str("env = 42\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["init", "--force", "--config", "companion.toml"]);
+	cmd.assert()
+	   .success()
+	   .stderr(predicate::str::contains("using the default settings"));
+
+	directory.child("companion.toml").assert(predicate::str::contains("env = \".env\""));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_init_outside_of_template() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.current_dir(directory.path())
+	   .args(["init"]);
+	cmd.assert()
+	   .code(exitcode::CONFIG)
+	   .stderr(predicate::str::contains("No SaaS template found"));
+
+	directory.child("companion.toml").assert(predicate::path::missing());
+
+	Ok(())
+}
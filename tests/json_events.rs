@@ -0,0 +1,79 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+
+/// Parse every stdout line of a json run as an event
+fn events(cmd: &mut Command) -> Result<(bool, Vec<serde_json::Value>), Box<dyn std::error::Error>> {
+	let output = cmd.output()?;
+	let events = String::from_utf8(output.stdout)?
+		.lines()
+		.map(serde_json::from_str)
+		.collect::<Result<Vec<serde_json::Value>, _>>()?;
+
+	Ok((output.status.success(), events))
+}
+
+#[test]
+fn can_emit_json_events_ending_with_a_result() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--json", "--env", file.path().to_str().unwrap()]);
+	let (success, events) = events(&mut cmd)?;
+	assert!(success);
+
+	for event in &events {
+		assert_eq!(event["version"], 1);
+		assert_eq!(event["command"], "make keys");
+		assert!(event["ts"].is_string());
+		assert!(event["level"].is_string());
+	}
+
+	let (result, logs) = events.split_last().unwrap();
+	assert!(logs.iter().all(|event| event["event"] == "log" && event["data"]["message"].is_string()));
+	assert_eq!(result["event"], "result");
+	assert_eq!(result["level"], "info");
+	assert_eq!(result["data"]["variables"]["next_auth_secret"]["updated"], true);
+
+	Ok(())
+}
+
+#[test]
+fn can_emit_json_result_on_failure() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str("NEXTAUTH_SECRET=\"\"\n").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--verify", "--json", "--env", file.path().to_str().unwrap()]);
+	let (success, events) = events(&mut cmd)?;
+	assert!(!success);
+
+	let result = events.last().unwrap();
+	assert_eq!(result["event"], "result");
+	assert_eq!(result["level"], "error");
+	assert!(result["data"]["error"]["message"].as_str().unwrap().contains("are invalid"));
+	assert_eq!(result["data"]["error"]["kind"], "validation");
+	assert_eq!(result["data"]["error"]["exit_code"], 1);
+	assert!(result["data"]["verification"]["results"].is_array());
+
+	Ok(())
+}
+
+#[test]
+fn can_emit_json_looking_values_as_strings() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str("OBJECT='{\"a\":1}'\nARRAY='[1,2]'\n").unwrap();
+
+	for (name, value) in [("OBJECT", "{\"a\":1}"), ("ARRAY", "[1,2]")] {
+		let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+		cmd.args(["env", "get", name, "--json", "--env", file.path().to_str().unwrap()]);
+		let (success, events) = events(&mut cmd)?;
+		assert!(success);
+
+		let result = events.last().unwrap();
+		assert_eq!(result["data"]["value"], value);
+	}
+
+	Ok(())
+}
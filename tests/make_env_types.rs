@@ -0,0 +1,44 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+use saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET;
+
+#[test]
+fn can_make_env_types() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	let example = directory.child(".env.example");
+	example.write_str("DATABASE_URL=\"\"\nNEXT_PUBLIC_APP_URL=\"\"\nGITHUB_SECRET=\"\"\n").unwrap();
+	let schema = directory.child("env.schema.toml");
+	schema.write_str(
+		"[variables.DATABASE_URL]\ntype = \"url\"\nrequired = true\ndescription = \"Connection string of the database\"\n\n\
+		 [variables.GITHUB_SECRET]\ntype = \"string\"\nrequired = [\"production\"]\n"
+	).unwrap();
+	let declarations = directory.child("env.d.ts");
+	let validator = directory.child("env.ts");
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args([
+		"make", "env-types",
+		"--example", example.path().to_str().unwrap(),
+		"--schema", schema.path().to_str().unwrap(),
+		"--declarations", declarations.path().to_str().unwrap(),
+		"--validator", validator.path().to_str().unwrap(),
+	]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] Zod environment validator written to"));
+
+	declarations.assert(predicate::str::contains("interface ProcessEnv"))
+	            .assert(predicate::str::contains("/** Connection string of the database */"))
+	            .assert(predicate::str::contains("GITHUB_SECRET?: string;"))
+	            .assert(predicate::str::contains(format!("{}: string;", ENV_VARIABLE__NEXTAUTH_SECRET)));
+	validator.assert(predicate::str::contains("DATABASE_URL: z.string().url(),"))
+	         .assert(predicate::str::contains("GITHUB_SECRET: process.env.NODE_ENV === \"production\" ? z.string() : z.string().optional(),"))
+	         .assert(predicate::str::contains("client: {\n\t\tNEXT_PUBLIC_APP_URL: z.string(),\n\t},"))
+	         .assert(predicate::str::contains(format!("{}: process.env.{},", ENV_VARIABLE__NEXTAUTH_SECRET, ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	Ok(())
+}
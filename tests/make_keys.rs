@@ -20,7 +20,7 @@ fn can_make_keys_in_dry_run_mode() -> Result<(), Box<dyn std::error::Error>> {
 	   .stdout(predicate::str::contains(saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET))
 	   .stdout(predicate::str::contains(saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY))
 	   .stdout(predicate::str::contains(saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY))
-	   .stdout(predicate::str::contains("[WARN] Dry run, skipping file update"));
+	   .stderr(predicate::str::contains("[WARN] Dry run, skipping file update"));
 
 	Ok(())
 }
@@ -30,7 +30,7 @@ fn can_make_keys_and_update_env_file() -> Result<(), Box<dyn std::error::Error>>
 	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
 
 	let file = assert_fs::NamedTempFile::new(".env").unwrap();
-	file.write_str(&*format!(
+	file.write_str(&format!(
 		"{}={}\n{}={}\n{}={}\nUNMUTATED_VARIABLE=UNMUTATED_VALUE\n",
 		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
 		"ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE",  // this value will be replaced
@@ -89,3 +89,566 @@ fn can_make_keys_and_create_env_file() -> Result<(), Box<dyn std::error::Error>>
 
 	Ok(())
 }
+
+#[test]
+fn can_make_keys_and_write_jwks_file() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	let jwks = assert_fs::NamedTempFile::new("jwks.json").unwrap();
+
+	cmd.args([
+		"make",
+		"keys",
+		"--env",
+		file.path().to_str().unwrap(),
+		"--format",
+		"jwk",
+		"--jwks-out",
+		jwks.path().to_str().unwrap(),
+	]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("\"kty\": \"OKP\""))
+	   .stdout(predicate::str::contains("\"crv\": \"X25519\""))
+	   .stdout(predicate::str::contains("[INFO] JSON Web Key Set written to"));
+
+	// private keys are only included on request
+	jwks.assert(predicate::str::contains("\"kid\""))
+	    .assert(predicate::str::contains("\"x\""))
+	    .assert(predicate::str::contains("\"d\"").not());
+
+	Ok(())
+}
+
+#[test]
+fn can_write_jwks_file_of_existing_keys() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	let jwks = assert_fs::NamedTempFile::new("jwks.json").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	let content = std::fs::read_to_string(file.path())?;
+	let public_key = content.lines()
+	                        .find_map(|line| line.strip_prefix(&format!("{}=", saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY)))
+	                        .unwrap()
+	                        .trim_matches('"')
+	                        .trim_end_matches('=')
+	                        .to_owned();
+
+	// nothing left to generate, the key set is built from the stored keypair, without the padding
+	// of the stored value
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--skip-existing", "--env", file.path().to_str().unwrap(), "--jwks-out", jwks.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] JSON Web Key Set written to"));
+
+	jwks.assert(predicate::str::contains(format!("\"x\": \"{}\"", public_key)));
+	file.assert(content);
+
+	Ok(())
+}
+
+#[test]
+fn cannot_write_jwks_file_without_public_key() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str("NEXTAUTH_URL=http://localhost:3000\n").unwrap();
+	let jwks = assert_fs::NamedTempFile::new("jwks.json").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args([
+		"make",
+		"keys",
+		"--env",
+		file.path().to_str().unwrap(),
+		"--only",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+		"--jwks-out",
+		jwks.path().to_str().unwrap(),
+	]);
+	cmd.assert()
+	   .failure()
+	   .code(saas_template_companion::error::VALIDATION_FAILED)
+	   .stderr(predicate::str::contains(format!(
+		   "{} is not set",
+		   saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+	   )));
+
+	jwks.assert(predicate::path::missing());
+	file.assert("NEXTAUTH_URL=http://localhost:3000\n");
+
+	Ok(())
+}
+
+#[test]
+fn can_make_only_selected_keys() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!(
+		"{}={}\n{}={}\n{}={}\n",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+		"ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE",  // this value will be replaced
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		"ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY__SAMPLE_VALUE",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		"ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY__SAMPLE_VALUE",
+	)).unwrap();
+
+	cmd.args([
+		"make",
+		"keys",
+		"--env",
+		file.path().to_str().unwrap(),
+		"--only",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+	]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] Generating symmetric encryption keys"))
+	   .stdout(predicate::str::contains("[INFO] Generating asymmetric encryption keys").not());
+
+	file.assert(predicate::str::contains("ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE").not())
+	    .assert(predicate::str::contains("ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY__SAMPLE_VALUE"))
+	    .assert(predicate::str::contains("ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY__SAMPLE_VALUE"));
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_skipping_existing_values() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!(
+		"{}={}\n{}=\"\"\n",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+		"ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+	)).unwrap();
+
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap(), "--skip-existing"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] Generating asymmetric encryption keys"))
+	   .stdout(predicate::str::contains("[INFO] Generating symmetric encryption keys").not());
+
+	file.assert(predicate::str::contains("ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE"))
+	    .assert(predicate::str::contains(format!(
+		    "{}=\"\"",
+		    saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY
+	    )).not())
+	    .assert(predicate::str::contains(saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY));
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_recovering_missing_public_key() -> Result<(), Box<dyn std::error::Error>> {
+	use saas_template_companion::helpers::base64_url;
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	let keypair = alkali::asymmetric::kx::Keypair::generate()?;
+	let private_key = base64_url(keypair.private_key.as_slice())?;
+	let public_key = base64_url(keypair.public_key.as_slice())?;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!(
+		"{}=\"\"\n{}=\"{}\"\n",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		private_key,
+	)).unwrap();
+
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap(), "--skip-existing"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] Recovering the asymmetric public key from the private key"))
+	   .stdout(predicate::str::contains("[INFO] Generating asymmetric encryption keys").not());
+
+	file.assert(predicate::str::contains(format!(
+		"{}=\"{}\"",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		public_key,
+	))).assert(predicate::str::contains(format!(
+		"{}=\"{}\"",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		private_key,
+	)));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_make_keys_completing_lone_public_key() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	let content = format!(
+		"{}=\"PUBLIC_KEY\"\n{}=\"\"\n",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+	);
+	file.write_str(&content).unwrap();
+
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap(), "--skip-existing"]);
+	cmd.assert()
+	   .failure()
+	   .code(saas_template_companion::error::VALIDATION_FAILED)
+	   .stderr(predicate::str::contains("the private key cannot be recovered"));
+
+	file.assert(content);
+
+	Ok(())
+}
+
+#[test]
+fn can_verify_generated_keys() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--verify", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("Valid"))
+	   .stdout(predicate::str::contains("[INFO] Encryption keys verified successfully"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_verify_placeholder_keys() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!(
+		"{}=\"\"\n",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+	)).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--verify", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .failure()
+	   .stdout(predicate::str::contains("Placeholder"))
+	   .stdout(predicate::str::contains("Missing"))
+	   .stderr(predicate::str::contains("[ERROR] Encryption keys verification failed"));
+
+	Ok(())
+}
+
+#[test]
+fn can_derive_identical_keys_from_master_secret() -> Result<(), Box<dyn std::error::Error>> {
+	let first = assert_fs::NamedTempFile::new(".env").unwrap();
+	let second = assert_fs::NamedTempFile::new(".env").unwrap();
+
+	for file in [&first, &second] {
+		let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+		cmd.env("MASTER_SECRET", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+		cmd.args([
+			"make",
+			"keys",
+			"--env",
+			file.path().to_str().unwrap(),
+			"--derive-from",
+			"MASTER_SECRET",
+			"--derive-context",
+			"pr-42",
+		]);
+		cmd.assert()
+		   .success()
+		   .stdout(predicate::str::contains("[INFO] Deriving asymmetric encryption keys for context 'pr-42'"));
+	}
+
+	assert_eq!(std::fs::read_to_string(first.path())?, std::fs::read_to_string(second.path())?);
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_with_masked_secrets() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+
+	for json in [false, true] {
+		let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+		cmd.args(["make", "keys", "--env", file.path().to_str().unwrap()]);
+		if json {
+			cmd.arg("--json");
+		}
+		let output = cmd.output()?;
+		assert!(output.status.success());
+
+		let stdout = String::from_utf8(output.stdout)?;
+		let content = std::fs::read_to_string(file.path())?;
+		let value = |name: &str| content.lines()
+		                                .find_map(|line| line.strip_prefix(&format!("{}=\"", name)))
+		                                .map(|value| value.trim_end_matches('"').to_owned())
+		                                .unwrap();
+
+		assert!(!stdout.contains(&value(saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET)));
+		assert!(!stdout.contains(&value(saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY)));
+		assert!(stdout.contains(&value(saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY)));
+		assert!(stdout.contains("********#"));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_with_revealed_secrets() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--dry-run", "--reveal"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("********#").not());
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_with_machine_readable_output() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap(), "--output", "shell"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::starts_with(format!("export {}='", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET)))
+	   .stdout(predicate::str::contains("[INFO]").not())
+	   .stderr(predicate::str::contains("[INFO] .env file update completed"));
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--dry-run", "--output", "kubernetes-secret", "--kubernetes-secret-name", "app-keys"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("kind: Secret"))
+	   .stdout(predicate::str::contains("name: app-keys"));
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_for_github_actions() -> Result<(), Box<dyn std::error::Error>> {
+	let github_env = assert_fs::NamedTempFile::new("github_env").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.env("GITHUB_ENV", github_env.path());
+	cmd.args(["make", "keys", "--dry-run", "--output", "github-actions"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::starts_with("::add-mask::"))
+	   .stdout(predicate::str::contains(format!("{}=", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.env("GITHUB_ENV", github_env.path());
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap(), "--output", "github-actions"]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("::add-mask::"))
+	   .stdout(predicate::str::contains(format!("{}=", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET)).not());
+
+	github_env.assert(predicate::str::contains(format!("{}=", saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY)));
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_for_multiple_env_files() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	directory.child("apps/web/.env").write_str("DATABASE_URL=\"postgres://localhost/web\"\n").unwrap();
+	directory.child("apps/worker/.env").write_str("").unwrap();
+	directory.child("apps/admin").create_dir_all().unwrap();
+	let admin = directory.child("apps/admin/.env");
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args([
+		"make", "keys",
+		"--env", directory.child("apps/*/.env").path().to_str().unwrap(),
+		"--env", admin.path().to_str().unwrap(),
+	]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("web/.env updated"))
+	   .stdout(predicate::str::contains("worker/.env updated"))
+	   .stdout(predicate::str::contains("admin/.env created"))
+	   .stdout(predicate::str::contains("[INFO] .env file update completed, 1 created, 2 updated"));
+
+	let web = std::fs::read_to_string(directory.child("apps/web/.env").path())?;
+	let worker = std::fs::read_to_string(directory.child("apps/worker/.env").path())?;
+	assert!(web.starts_with("DATABASE_URL=\"postgres://localhost/web\"\n"));
+	assert_eq!(web.trim_start_matches("DATABASE_URL=\"postgres://localhost/web\"\n"), worker);
+	assert_eq!(std::fs::read_to_string(admin.path())?, worker);
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--verify", "--env", directory.child("apps/*/.env").path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("Encryption keys verified successfully in").count(3));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_make_keys_skipping_existing_values_that_differ_across_env_files() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	let populated = format!("{}=\"EXISTING_SECRET\"\n", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET);
+	let missing = format!("{}=\"\"\n", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET);
+	directory.child("a.env").write_str(&populated).unwrap();
+	directory.child("b.env").write_str(&missing).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args([
+		"make", "keys",
+		"--skip-existing",
+		"--only", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+		"--env", &format!("{},{}", directory.child("a.env").path().display(), directory.child("b.env").path().display()),
+	]);
+	cmd.assert()
+	   .failure()
+	   .code(saas_template_companion::error::VALIDATION_FAILED)
+	   .stderr(predicate::str::contains("The environment files are missing different keys"))
+	   .stderr(predicate::str::contains("a.env needs nothing"));
+
+	directory.child("a.env").assert(populated.as_str());
+	directory.child("b.env").assert(missing.as_str());
+
+	Ok(())
+}
+
+#[test]
+fn can_inspect_keys_across_env_files() -> Result<(), Box<dyn std::error::Error>> {
+	let staging = assert_fs::NamedTempFile::new(".env.staging").unwrap();
+	let production = assert_fs::NamedTempFile::new(".env.production").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", staging.path().to_str().unwrap(), "--env", production.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "inspect", "--env", staging.path().to_str().unwrap(), "--env", production.path().to_str().unwrap()]);
+	let output = cmd.output()?;
+	assert!(output.status.success());
+
+	let stdout = String::from_utf8(output.stdout)?;
+	let content = std::fs::read_to_string(staging.path())?;
+	assert!(stdout.contains("x25519 private"));
+	assert!(stdout.contains("matching"));
+	assert!(String::from_utf8(output.stderr)?.contains("[WARN] NEXTAUTH_SECRET in"));
+	assert!(content.lines().all(|line| !stdout.contains(line.split_once('=').unwrap().1.trim_matches('"'))));
+
+	Ok(())
+}
+
+#[test]
+fn can_route_warnings_to_stderr_and_log_to_file() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	let log_file = assert_fs::NamedTempFile::new("companion.log").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--dry-run", "--env", file.path().to_str().unwrap(), "--log-file", log_file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("[INFO] Generating symmetric encryption keys"))
+	   .stdout(predicate::str::contains("[WARN]").not())
+	   .stderr(predicate::str::contains("[WARN] Dry run, skipping file update"))
+	   .stderr(predicate::str::contains("[INFO]").not());
+
+	let logs = std::fs::read_to_string(log_file.path())?;
+	assert!(logs.contains("[INFO] Generating symmetric encryption keys"));
+	assert!(logs.contains("[WARN] Dry run, skipping file update"));
+
+	Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn can_restrict_env_file_permissions() -> Result<(), Box<dyn std::error::Error>> {
+	use std::os::unix::fs::PermissionsExt;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	let mode = || file.path().metadata().unwrap().permissions().mode() & 0o777;
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success();
+	assert_eq!(mode(), 0o600);
+
+	std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o644))?;
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stderr(predicate::str::contains("is readable by other users (644), run with --fix-permissions"));
+	assert_eq!(mode(), 0o644);
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "--fix-permissions", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("restricted from 644 to 600"));
+	assert_eq!(mode(), 0o600);
+
+	Ok(())
+}
+
+#[test]
+fn can_make_keys_filtering_stdin_to_stdout() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	cmd.args(["make", "keys", "--env", "-"]);
+	cmd.write_stdin(format!(
+		"# comment\n{}=ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE\nUNMUTATED_VARIABLE=UNMUTATED_VALUE\n",
+		saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET,
+	));
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::starts_with("# comment\n"))
+	   .stdout(predicate::str::contains(format!("{}=\"", saas_template_companion::make::keys::constants::ENV_VARIABLE__NEXTAUTH_SECRET)))
+	   .stdout(predicate::str::contains(format!("{}=\"", saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY)))
+	   .stdout(predicate::str::contains("UNMUTATED_VARIABLE=UNMUTATED_VALUE\n"))
+	   .stdout(predicate::str::contains("ENV_VARIABLE__NEXTAUTH_SECRET__SAMPLE_VALUE").not())
+	   .stdout(predicate::str::contains("[INFO]").not())
+	   .stderr(predicate::str::contains("[INFO] Encryption keys created successfully"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_make_keys_writing_both_keys_and_env_file_to_stdout() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	cmd.args(["make", "keys", "--env", "-", "--output", "dotenv"]);
+	cmd.write_stdin("");
+	cmd.assert()
+	   .failure()
+	   .code(78)
+	   .stderr(predicate::str::contains("Cannot write both the generated keys and the environment file to stdout"));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_make_keys_recovering_public_key_from_malformed_private_key() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!("{}=\"not base64!\"\n", saas_template_companion::make::keys::constants::ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY)).unwrap();
+
+	cmd.args(["make", "keys", "--skip-existing", "--env", file.path().to_str().unwrap()]);
+	cmd.assert()
+	   .code(exitcode::DATAERR)
+	   .stderr(predicate::str::contains("DecodeError"));
+
+	Ok(())
+}
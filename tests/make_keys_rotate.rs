@@ -0,0 +1,126 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+use saas_template_companion::make::keys::constants::{
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+	ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+	ENV_VARIABLE__NEXTAUTH_SECRET,
+};
+
+#[test]
+fn can_rotate_and_rollback_keys() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!(
+		"{}=\"{}\"\n{}=\"{}\"\n{}=\"{}\"\n",
+		ENV_VARIABLE__NEXTAUTH_SECRET,
+		"CURRENT_SECRET",
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PUBLIC_KEY,
+		"CURRENT_PUBLIC_KEY",
+		ENV_VARIABLE__ASYMMETRIC_ENCRYPTION_PRIVATE_KEY,
+		"CURRENT_PRIVATE_KEY",
+	)).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "rotate", "--env", file.path().to_str().unwrap(), "--only", ENV_VARIABLE__NEXTAUTH_SECRET]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains(format!("[INFO] Rotated {}", ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	file.assert(predicate::str::contains(format!("{}_PREVIOUS=\"CURRENT_SECRET\"", ENV_VARIABLE__NEXTAUTH_SECRET)))
+	    .assert(predicate::str::contains(format!("{}=\"CURRENT_SECRET\"", ENV_VARIABLE__NEXTAUTH_SECRET)).not())
+	    .assert(predicate::str::contains("CURRENT_PUBLIC_KEY"))
+	    .assert(predicate::str::contains("CURRENT_PRIVATE_KEY"));
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "rollback", "--env", file.path().to_str().unwrap(), "--only", ENV_VARIABLE__NEXTAUTH_SECRET]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains(format!("[INFO] Rolled back {}", ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	file.assert(predicate::str::contains(format!("{}=\"CURRENT_SECRET\"", ENV_VARIABLE__NEXTAUTH_SECRET)))
+	    .assert(predicate::str::contains(format!("{}_PREVIOUS=\"\"", ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_rollback_without_previous_generation() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!("{}=\"CURRENT_SECRET\"\n", ENV_VARIABLE__NEXTAUTH_SECRET)).unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["make", "keys", "rollback", "--env", file.path().to_str().unwrap(), "--only", ENV_VARIABLE__NEXTAUTH_SECRET]);
+	cmd.assert()
+	   .failure()
+	   .stderr(predicate::str::contains("No previous generation"));
+
+	Ok(())
+}
+
+#[test]
+fn can_rotate_keys_with_history() -> Result<(), Box<dyn std::error::Error>> {
+	let file = assert_fs::NamedTempFile::new(".env").unwrap();
+	file.write_str(&format!("{}=\"CURRENT_SECRET\"\n", ENV_VARIABLE__NEXTAUTH_SECRET)).unwrap();
+	let history = assert_fs::NamedTempFile::new("history.jsonl").unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.env("SAAS_COMPANION_HISTORY_KEY", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+	cmd.args([
+		"make",
+		"keys",
+		"rotate",
+		"--env",
+		file.path().to_str().unwrap(),
+		"--generations",
+		"2",
+		"--history",
+		history.path().to_str().unwrap(),
+	]);
+	cmd.assert()
+	   .success();
+
+	file.assert(predicate::str::contains(format!("{}_PREVIOUS_1=\"CURRENT_SECRET\"", ENV_VARIABLE__NEXTAUTH_SECRET)))
+	    .assert(predicate::str::contains(format!("{}_PREVIOUS_2=\"\"", ENV_VARIABLE__NEXTAUTH_SECRET)));
+
+	// the history is encrypted, values must never be stored in plain text
+	history.assert(predicate::str::contains("\"nonce\""))
+	       .assert(predicate::str::contains("CURRENT_SECRET").not());
+
+	Ok(())
+}
+
+#[test]
+fn cannot_rotate_keys_without_history_key() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	let content = format!("{}=\"CURRENT_SECRET\"\n", ENV_VARIABLE__NEXTAUTH_SECRET);
+	for name in ["a.env", "b.env"] {
+		directory.child(name).write_str(&content).unwrap();
+	}
+	let history = directory.child("history.jsonl");
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.env_remove("SAAS_COMPANION_HISTORY_KEY");
+	cmd.args([
+		"make",
+		"keys",
+		"rotate",
+		"--env",
+		&format!("{},{}", directory.child("a.env").path().display(), directory.child("b.env").path().display()),
+		"--history",
+		history.path().to_str().unwrap(),
+	]);
+	cmd.assert()
+	   .failure()
+	   .code(78)
+	   .stderr(predicate::str::contains("Cannot read the rotation history key from the SAAS_COMPANION_HISTORY_KEY environment variable"));
+
+	// nothing is rotated when the history cannot be kept
+	directory.child("a.env").assert(content.as_str());
+	directory.child("b.env").assert(content.as_str());
+	history.assert(predicate::path::missing());
+
+	Ok(())
+}
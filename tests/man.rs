@@ -0,0 +1,23 @@
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+#[test]
+fn can_generate_man_pages() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["man", "--output", directory.path().to_str().unwrap()]);
+	cmd.assert()
+	   .success()
+	   .stdout(predicate::str::contains("man pages written to"));
+
+	directory.child("saas-template-companion.1").assert(predicate::path::exists());
+	directory.child("saas-template-companion-make-signatures.1").assert(predicate::path::exists());
+	directory.child("saas-template-companion-make-keys.1")
+	         .assert(predicate::str::contains(".TH saas-template-companion-make-keys 1"));
+
+	Ok(())
+}
@@ -0,0 +1,45 @@
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Build a PATH looking up the given directory first
+fn path_with(directory: &std::path::Path) -> std::ffi::OsString {
+	let mut paths = vec![directory.to_path_buf()];
+	paths.extend(std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()));
+	std::env::join_paths(paths).unwrap()
+}
+
+#[test]
+fn can_run_external_subcommands() -> Result<(), Box<dyn std::error::Error>> {
+	let directory = assert_fs::TempDir::new().unwrap();
+	let plugin = directory.child("saas-template-companion-stripe");
+	plugin.write_str("#!/bin/sh\necho \"dry_run=$SAAS_TEMPLATE_COMPANION_DRY_RUN args=$*\"\ncat\nexit 3\n").unwrap();
+	std::fs::set_permissions(plugin.path(), std::fs::Permissions::from_mode(0o755))?;
+
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.env("PATH", path_with(directory.path()))
+	   .args(["--dry-run", "stripe", "charge", "--amount", "10"]);
+	cmd.assert()
+	   .code(3)
+	   .stdout(predicate::str::contains("dry_run=true args=charge --amount 10"))
+	   .stdout(predicate::str::contains(r#""command":"stripe","args":["charge","--amount","10"]"#))
+	   .stdout(predicate::str::contains(r#""log_level":"info""#));
+
+	Ok(())
+}
+
+#[test]
+fn cannot_run_unknown_external_subcommands() -> Result<(), Box<dyn std::error::Error>> {
+	let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+	cmd.args(["not-a-plugin"]);
+	cmd.assert()
+	   .code(exitcode::CONFIG)
+	   .stderr(predicate::str::contains("no saas-template-companion-not-a-plugin executable found on PATH"));
+
+	Ok(())
+}